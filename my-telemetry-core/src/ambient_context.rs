@@ -0,0 +1,176 @@
+use std::future::Future;
+
+use tokio::task::futures::TaskLocalFuture;
+
+use crate::{EventDurationTracker, MyTelemetryContext};
+
+tokio::task_local! {
+    static CURRENT_TELEMETRY_CONTEXT: AmbientTelemetryContext;
+}
+
+// Span id is set when the scope is the span of the tracker. Events started inside of it are
+// its children. Sampled flag is taken from the interface of the tracker
+#[derive(Debug, Clone)]
+pub struct AmbientTelemetryContext {
+    pub my_telemetry: MyTelemetryContext,
+    pub span_id: Option<i64>,
+    pub trace_state: Option<String>,
+    pub sampled: bool,
+}
+
+impl AmbientTelemetryContext {
+    pub fn current() -> Option<AmbientTelemetryContext> {
+        CURRENT_TELEMETRY_CONTEXT.try_with(|ctx| ctx.clone()).ok()
+    }
+}
+
+impl From<MyTelemetryContext> for AmbientTelemetryContext {
+    fn from(my_telemetry: MyTelemetryContext) -> Self {
+        Self {
+            sampled: my_telemetry.is_sampled(),
+            my_telemetry,
+            span_id: None,
+            trace_state: None,
+        }
+    }
+}
+
+impl From<&EventDurationTracker> for AmbientTelemetryContext {
+    fn from(tracker: &EventDurationTracker) -> Self {
+        Self {
+            my_telemetry: tracker.my_telemetry.clone(),
+            span_id: Some(tracker.span_id),
+            trace_state: tracker.trace_state.clone(),
+            sampled: tracker
+                .get_telemetry_interface()
+                .is_sampled(&tracker.my_telemetry),
+        }
+    }
+}
+
+impl MyTelemetryContext {
+    pub fn current() -> Option<MyTelemetryContext> {
+        CURRENT_TELEMETRY_CONTEXT
+            .try_with(|ctx| ctx.my_telemetry.clone())
+            .ok()
+    }
+
+    pub fn current_span_id() -> Option<i64> {
+        CURRENT_TELEMETRY_CONTEXT
+            .try_with(|ctx| ctx.span_id)
+            .ok()
+            .flatten()
+    }
+
+    pub fn current_or_new() -> MyTelemetryContext {
+        match Self::current() {
+            Some(ctx) => ctx,
+            None => MyTelemetryContext::Single(crate::generate_process_id()),
+        }
+    }
+
+    pub async fn scope<TFuture: Future>(self, future: TFuture) -> TFuture::Output {
+        CURRENT_TELEMETRY_CONTEXT.scope(self.into(), future).await
+    }
+
+    pub fn sync_scope<TResult>(self, func: impl FnOnce() -> TResult) -> TResult {
+        CURRENT_TELEMETRY_CONTEXT.sync_scope(self.into(), func)
+    }
+}
+
+impl EventDurationTracker {
+    pub async fn scope<TFuture: Future>(&self, future: TFuture) -> TFuture::Output {
+        CURRENT_TELEMETRY_CONTEXT.scope(self.into(), future).await
+    }
+
+    pub fn sync_scope<TResult>(&self, func: impl FnOnce() -> TResult) -> TResult {
+        CURRENT_TELEMETRY_CONTEXT.sync_scope(self.into(), func)
+    }
+}
+
+pub async fn with_context<TFuture: Future>(
+    ctx: &MyTelemetryContext,
+    future: TFuture,
+) -> TFuture::Output {
+    ctx.clone().scope(future).await
+}
+
+pub trait MyTelemetryFutureExt: Future + Sized {
+    fn with_telemetry(
+        self,
+        ctx: MyTelemetryContext,
+    ) -> TaskLocalFuture<AmbientTelemetryContext, Self> {
+        CURRENT_TELEMETRY_CONTEXT.scope(ctx.into(), self)
+    }
+
+    // Events started inside of the future are the children of the tracker
+    fn with_tracker(
+        self,
+        tracker: &EventDurationTracker,
+    ) -> TaskLocalFuture<AmbientTelemetryContext, Self> {
+        CURRENT_TELEMETRY_CONTEXT.scope(tracker.into(), self)
+    }
+
+    fn with_current_telemetry(self) -> TaskLocalFuture<AmbientTelemetryContext, Self> {
+        let ctx = match AmbientTelemetryContext::current() {
+            Some(ctx) => ctx,
+            None => MyTelemetryContext::current_or_new().into(),
+        };
+
+        CURRENT_TELEMETRY_CONTEXT.scope(ctx, self)
+    }
+}
+
+impl<TFuture: Future> MyTelemetryFutureExt for TFuture {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tracker_started_in_tracker_scope_is_child() {
+        let ctx = MyTelemetryContext::Single(1_700_000_000_000_000);
+        let parent = ctx.start_event_tracking("parent");
+
+        let child = parent
+            .scope(async { EventDurationTracker::new("child", None) })
+            .await;
+
+        assert_eq!(child.my_telemetry.as_string(), ctx.as_string());
+        assert_eq!(child.parent_span_id, Some(parent.span_id));
+
+        let grand_child = child.sync_scope(|| EventDurationTracker::new("grand-child", None));
+
+        assert_eq!(grand_child.parent_span_id, Some(child.span_id));
+    }
+
+    #[tokio::test]
+    async fn test_context_scope_has_no_span() {
+        let ctx = MyTelemetryContext::Single(1_700_000_000_000_000);
+
+        let tracker = async { EventDurationTracker::new("event", None) }
+            .with_telemetry(ctx.clone())
+            .await;
+
+        assert_eq!(tracker.parent_span_id, None);
+        assert_eq!(
+            tracker.get_parent_span_id(1_700_000_000_000_000),
+            Some(1_700_000_000_000_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_track_async_runs_in_span_of_event() {
+        let ctx = MyTelemetryContext::Single(1_700_000_000_000_000);
+
+        let span_id = ctx
+            .track_async("event", async {
+                Ok::<_, String>(MyTelemetryContext::current_span_id())
+            })
+            .await
+            .unwrap();
+
+        assert!(span_id.is_some());
+        assert_ne!(span_id, Some(1_700_000_000_000_000));
+    }
+}
@@ -16,7 +16,7 @@ impl MyTelemetryContext {
 
     #[deprecated(note = "Use MyTelemetryContext::start_duration_tracking('my-process-name')")]
     pub fn new() -> Self {
-        Self::Single(DateTimeAsMicroseconds::now().unix_microseconds)
+        Self::Single(crate::TELEMETRY_INTERFACE.generate_process_id(""))
     }
 
     #[deprecated(note = "Use MyTelemetryContext::start_duration_tracking('my-process-name')")]
@@ -38,12 +38,16 @@ impl MyTelemetryContext {
     ) -> EventDurationTracker {
         EventDurationTracker {
             my_telemetry: self.clone(),
+            span_id: crate::generate_span_id(),
+            parent_span_id: None,
             event_name: Some(event_name.into()),
             started: DateTimeAsMicroseconds::now(),
             ok_result: None,
             fail_result: None,
             tags: None,
+            trace_state: None,
             ignore_this_event: false,
+            telemetry_interface: None,
         }
     }
 
@@ -182,7 +186,7 @@ impl Into<MyTelemetryContext> for Option<&MyTelemetryContext> {
         if let Some(ctx) = self {
             ctx.clone()
         } else {
-            MyTelemetryContext::Single(DateTimeAsMicroseconds::now().unix_microseconds).into()
+            MyTelemetryContext::current_or_new()
         }
     }
 }
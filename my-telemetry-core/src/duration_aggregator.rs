@@ -0,0 +1,394 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+
+use crate::{
+    TelemetryEventTag, TelemetryHistogram, TelemetryMetric, TelemetryMetricValue,
+    DEFAULT_HISTOGRAM_BOUNDS,
+};
+
+// Each power of two is split to 16 buckets, so percentiles are accurate within ~4.5%
+const BUCKETS_PER_POWER_OF_TWO: f64 = 16.0;
+
+pub const DEFAULT_MAX_EVENT_DURATION_SERIES: usize = 1_000;
+
+#[derive(Debug, Clone)]
+pub struct EventDurationStats {
+    pub event_name: String,
+    pub tags: Vec<TelemetryEventTag>,
+    pub count: u64,
+    pub fail_count: u64,
+    pub min: Duration,
+    pub max: Duration,
+    pub avg: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+impl EventDurationStats {
+    pub fn get_error_rate(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+
+        self.fail_count as f64 / self.count as f64
+    }
+}
+
+struct EventDurations {
+    event_name: String,
+    tags: Vec<TelemetryEventTag>,
+    started: i64,
+    updated: i64,
+    count: u64,
+    fail_count: u64,
+    sum_micros: u64,
+    min_micros: u64,
+    max_micros: u64,
+    buckets: BTreeMap<i32, u64>,
+    histogram: TelemetryHistogram,
+}
+
+impl EventDurations {
+    fn new(event_name: String, tags: Vec<TelemetryEventTag>, now: i64) -> Self {
+        Self {
+            event_name,
+            tags,
+            started: now,
+            updated: now,
+            count: 0,
+            fail_count: 0,
+            sum_micros: 0,
+            min_micros: 0,
+            max_micros: 0,
+            buckets: BTreeMap::new(),
+            histogram: TelemetryHistogram::new(DEFAULT_HISTOGRAM_BOUNDS.to_vec()),
+        }
+    }
+
+    fn record(&mut self, duration_micros: u64, is_fail: bool, now: i64) {
+        if self.count == 0 {
+            self.min_micros = duration_micros;
+            self.max_micros = duration_micros;
+        } else {
+            self.min_micros = self.min_micros.min(duration_micros);
+            self.max_micros = self.max_micros.max(duration_micros);
+        }
+
+        self.count += 1;
+        self.sum_micros += duration_micros;
+        self.updated = now;
+
+        if is_fail {
+            self.fail_count += 1;
+        }
+
+        *self
+            .buckets
+            .entry(get_bucket_index(duration_micros))
+            .or_insert(0) += 1;
+        self.histogram.record(duration_micros as f64 / 1_000_000.0);
+    }
+
+    fn get_percentile(&self, percentile: f64) -> Duration {
+        let rank = ((self.count as f64 * percentile).ceil() as u64).max(1);
+
+        let mut passed = 0;
+        for (index, amount) in &self.buckets {
+            passed += amount;
+
+            if passed >= rank {
+                let micros = get_bucket_upper_bound(*index).clamp(self.min_micros, self.max_micros);
+                return Duration::from_micros(micros);
+            }
+        }
+
+        Duration::from_micros(self.max_micros)
+    }
+
+    fn get_stats(&self) -> EventDurationStats {
+        EventDurationStats {
+            event_name: self.event_name.clone(),
+            tags: self.tags.clone(),
+            count: self.count,
+            fail_count: self.fail_count,
+            min: Duration::from_micros(self.min_micros),
+            max: Duration::from_micros(self.max_micros),
+            avg: Duration::from_micros(self.sum_micros / self.count.max(1)),
+            p50: self.get_percentile(0.50),
+            p95: self.get_percentile(0.95),
+            p99: self.get_percentile(0.99),
+        }
+    }
+
+    fn to_metrics(&self) -> Vec<TelemetryMetric> {
+        let stats = self.get_stats();
+
+        let mut tags = Vec::with_capacity(self.tags.len() + 1);
+        tags.push(TelemetryEventTag {
+            key: "event".to_string(),
+            value: self.event_name.clone(),
+        });
+        tags.extend(self.tags.iter().cloned());
+
+        let create_metric = |name: &str, value: TelemetryMetricValue| TelemetryMetric {
+            name: name.to_string(),
+            tags: tags.clone(),
+            value,
+            started: self.started,
+            updated: self.updated,
+        };
+
+        vec![
+            create_metric(
+                "event_duration_seconds",
+                TelemetryMetricValue::Histogram(self.histogram.clone()),
+            ),
+            create_metric(
+                "event_fails",
+                TelemetryMetricValue::Counter(self.fail_count),
+            ),
+            create_metric(
+                "event_duration_p50_seconds",
+                TelemetryMetricValue::Gauge(stats.p50.as_secs_f64()),
+            ),
+            create_metric(
+                "event_duration_p95_seconds",
+                TelemetryMetricValue::Gauge(stats.p95.as_secs_f64()),
+            ),
+            create_metric(
+                "event_duration_p99_seconds",
+                TelemetryMetricValue::Gauge(stats.p99.as_secs_f64()),
+            ),
+        ]
+    }
+}
+
+// Every event name and tag set is the separate series. New series above the limit are dropped
+// and counted, the reason is printed once
+pub struct EventDurationAggregator {
+    tag_keys: Vec<String>,
+    items: HashMap<String, EventDurations>,
+    max_series: Option<usize>,
+    dropped: u64,
+    series_limit_reported: bool,
+}
+
+impl EventDurationAggregator {
+    pub fn new() -> Self {
+        Self {
+            tag_keys: Vec::new(),
+            items: HashMap::new(),
+            max_series: Some(DEFAULT_MAX_EVENT_DURATION_SERIES),
+            dropped: 0,
+            series_limit_reported: false,
+        }
+    }
+
+    pub fn set_max_series(&mut self, max_series: Option<usize>) {
+        self.max_series = max_series;
+    }
+
+    // Durations which were not recorded because of the series limit
+    pub fn get_dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    // Only tags with these keys split the statistics of the event
+    pub fn set_tag_keys(&mut self, tag_keys: Vec<String>) {
+        self.tag_keys = tag_keys;
+        self.items.clear();
+    }
+
+    pub fn record(
+        &mut self,
+        event_name: &str,
+        tags: Option<&[TelemetryEventTag]>,
+        duration_micros: i64,
+        is_fail: bool,
+        now: i64,
+    ) {
+        let mut selected_tags = Vec::new();
+
+        if let Some(tags) = tags {
+            for tag_key in &self.tag_keys {
+                if let Some(tag) = tags.iter().find(|itm| &itm.key == tag_key) {
+                    selected_tags.push(tag.clone());
+                }
+            }
+        }
+
+        let mut key = event_name.to_string();
+        for tag in &selected_tags {
+            key.push('\u{0}');
+            key.push_str(&tag.key);
+            key.push('=');
+            key.push_str(&tag.value);
+        }
+
+        if !self.items.contains_key(&key) {
+            if let Some(max_series) = self.max_series {
+                if self.items.len() >= max_series {
+                    self.dropped += 1;
+                    if !self.series_limit_reported {
+                        self.series_limit_reported = true;
+                        println!(
+                            "Limit of {} event duration series is reached. New series are dropped",
+                            max_series
+                        );
+                    }
+                    return;
+                }
+            }
+        }
+
+        self.items
+            .entry(key)
+            .or_insert_with(|| EventDurations::new(event_name.to_string(), selected_tags, now))
+            .record(duration_micros.max(0) as u64, is_fail, now);
+    }
+
+    pub fn get_stats(&self) -> Vec<EventDurationStats> {
+        let mut result: Vec<EventDurationStats> =
+            self.items.values().map(|itm| itm.get_stats()).collect();
+        result.sort_by(|a, b| a.event_name.cmp(&b.event_name));
+        result
+    }
+
+    pub fn get_metrics(&self) -> Vec<TelemetryMetric> {
+        let mut result = Vec::new();
+
+        for item in self.items.values() {
+            result.extend(item.to_metrics());
+        }
+
+        result
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+impl Default for EventDurationAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn get_bucket_index(duration_micros: u64) -> i32 {
+    if duration_micros == 0 {
+        return -1;
+    }
+
+    ((duration_micros as f64).log2() * BUCKETS_PER_POWER_OF_TWO).floor() as i32
+}
+
+fn get_bucket_upper_bound(index: i32) -> u64 {
+    if index < 0 {
+        return 0;
+    }
+
+    2f64.powf((index + 1) as f64 / BUCKETS_PER_POWER_OF_TWO)
+        .ceil() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000_000;
+
+    fn create_tag(key: &str, value: &str) -> TelemetryEventTag {
+        TelemetryEventTag {
+            key: key.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn assert_close(duration: Duration, expected_millis: u64) {
+        let expected = expected_millis as f64 / 1000.0;
+        let diff = (duration.as_secs_f64() - expected).abs();
+        assert!(
+            diff <= expected * 0.05,
+            "{:?} != {}ms",
+            duration,
+            expected_millis
+        );
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut aggregator = EventDurationAggregator::new();
+
+        for millis in 1..=1000 {
+            aggregator.record("request", None, millis * 1000, millis % 10 == 0, NOW);
+        }
+
+        let stats = aggregator.get_stats();
+        assert_eq!(stats.len(), 1);
+
+        let stats = &stats[0];
+        assert_eq!(stats.count, 1000);
+        assert_eq!(stats.fail_count, 100);
+        assert_eq!(stats.get_error_rate(), 0.1);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.max, Duration::from_millis(1000));
+        assert_eq!(stats.avg, Duration::from_micros(500_500));
+        assert_close(stats.p50, 500);
+        assert_close(stats.p95, 950);
+        assert_close(stats.p99, 990);
+    }
+
+    #[test]
+    fn test_only_selected_tags_split_the_series() {
+        let mut aggregator = EventDurationAggregator::new();
+        aggregator.set_tag_keys(vec!["route".to_string()]);
+
+        for (route, client_id) in [("/a", "1"), ("/a", "2"), ("/b", "1")] {
+            let tags = [
+                create_tag("route", route),
+                create_tag("client_id", client_id),
+            ];
+            aggregator.record("request", Some(&tags), 1000, false, NOW);
+        }
+
+        let stats = aggregator.get_stats();
+        assert_eq!(stats.len(), 2);
+
+        let route_a = stats.iter().find(|itm| itm.tags[0].value == "/a").unwrap();
+        assert_eq!(route_a.count, 2);
+        assert_eq!(route_a.tags.len(), 1);
+
+        // Every series is exported as the histogram, the fails counter and three percentiles
+        assert_eq!(aggregator.get_metrics().len(), 10);
+    }
+
+    #[test]
+    fn test_stats_are_reset() {
+        let mut aggregator = EventDurationAggregator::new();
+
+        aggregator.record("request", None, 1000, false, NOW);
+        aggregator.clear();
+        assert!(aggregator.get_stats().is_empty());
+
+        aggregator.record("request", None, 1000, false, NOW);
+        aggregator.set_tag_keys(vec!["route".to_string()]);
+        assert!(aggregator.get_stats().is_empty());
+    }
+
+    #[test]
+    fn test_new_series_above_limit_are_dropped() {
+        let mut aggregator = EventDurationAggregator::new();
+        aggregator.set_max_series(Some(2));
+
+        for event_name in ["a", "b", "c", "a"] {
+            aggregator.record(event_name, None, 1000, false, NOW);
+        }
+
+        assert_eq!(aggregator.get_stats().len(), 2);
+        assert_eq!(aggregator.get_dropped(), 1);
+    }
+}
@@ -1,34 +1,128 @@
+use std::sync::Arc;
+
 use rust_extensions::{date_time::DateTimeAsMicroseconds, StrOrString};
 
-use crate::{my_telemetry_event::TelemetryEventTag, MyTelemetryContext, TelemetryEvent};
+use crate::{
+    my_telemetry_event::TelemetryEventTag, AmbientTelemetryContext, MyTelemetryContext,
+    TelemetryEvent, TelemetryInterface, W3cTraceContext,
+};
 
 pub struct EventDurationTracker {
     pub my_telemetry: MyTelemetryContext,
+    pub span_id: i64,
+    pub parent_span_id: Option<i64>,
     pub event_name: Option<StrOrString<'static>>,
     pub started: DateTimeAsMicroseconds,
     pub ok_result: Option<StrOrString<'static>>,
     pub fail_result: Option<String>,
     pub tags: Option<Vec<TelemetryEventTag>>,
+    // Tracestate entries of the other vendors, propagated with the headers of the tracker
+    pub trace_state: Option<String>,
     pub ignore_this_event: bool,
+    pub telemetry_interface: Option<Arc<TelemetryInterface>>,
 }
 
 impl EventDurationTracker {
     pub fn new(
         event_name: impl Into<StrOrString<'static>>,
         ok_result: Option<StrOrString<'static>>,
+    ) -> Self {
+        Self::start_process(event_name, ok_result, None)
+    }
+
+    // Starts the event in the ambient context or a new process of the interface
+    pub fn start_process(
+        event_name: impl Into<StrOrString<'static>>,
+        ok_result: Option<StrOrString<'static>>,
+        telemetry_interface: Option<Arc<TelemetryInterface>>,
     ) -> Self {
         let event_name = event_name.into();
+
+        if let Some(ctx) = AmbientTelemetryContext::current() {
+            let mut result = ctx.my_telemetry.start_event_tracking(event_name);
+            result.parent_span_id = ctx.span_id;
+            result.trace_state = ctx.trace_state;
+            result.ok_result = ok_result;
+            result.telemetry_interface = telemetry_interface;
+            return result;
+        }
+
         let now = DateTimeAsMicroseconds::now();
+        let process_id = match &telemetry_interface {
+            Some(telemetry_interface) => {
+                telemetry_interface.generate_process_id(event_name.as_str())
+            }
+            None => crate::TELEMETRY_INTERFACE.generate_process_id(event_name.as_str()),
+        };
+
         Self {
-            my_telemetry: MyTelemetryContext::Single(now.unix_microseconds),
+            my_telemetry: MyTelemetryContext::Single(process_id),
+            span_id: process_id,
+            parent_span_id: None,
             event_name: Some(event_name),
             started: now,
             ok_result,
             fail_result: None,
             tags: None,
+            trace_state: None,
             ignore_this_event: false,
+            telemetry_interface,
+        }
+    }
+    pub fn start_child(&self, event_name: impl Into<StrOrString<'static>>) -> Self {
+        Self {
+            my_telemetry: self.my_telemetry.clone(),
+            span_id: crate::generate_span_id(),
+            parent_span_id: Some(self.span_id),
+            event_name: Some(event_name.into()),
+            started: DateTimeAsMicroseconds::now(),
+            ok_result: None,
+            fail_result: None,
+            tags: None,
+            trace_state: self.trace_state.clone(),
+            ignore_this_event: false,
+            telemetry_interface: self.telemetry_interface.clone(),
+        }
+    }
+
+    pub fn with_telemetry_interface(
+        mut self,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        self.telemetry_interface = Some(telemetry_interface);
+        self
+    }
+
+    pub fn get_telemetry_interface(&self) -> &Arc<TelemetryInterface> {
+        match &self.telemetry_interface {
+            Some(telemetry_interface) => telemetry_interface,
+            None => &crate::TELEMETRY_INTERFACE,
         }
     }
+
+    // Tracker which is started directly from the process is the root span of the process.
+    // Trackers started from MyTelemetryContext are the children of the root span
+    pub fn get_parent_span_id(&self, process_id: i64) -> Option<i64> {
+        if self.parent_span_id.is_some() {
+            return self.parent_span_id;
+        }
+
+        if self.span_id == process_id {
+            return None;
+        }
+
+        Some(process_id)
+    }
+
+    pub fn to_w3c_trace_context(&self) -> W3cTraceContext {
+        let mut result = self.my_telemetry.to_w3c_trace_context();
+        result.parent_span_id = self.span_id as u64;
+        result.sampled = self
+            .get_telemetry_interface()
+            .is_sampled(&self.my_telemetry);
+        result
+    }
+
     pub fn set_fail_result(&mut self, result: String) {
         self.fail_result = Some(result);
         self.ok_result = None;
@@ -50,6 +144,15 @@ impl EventDurationTracker {
         key: impl Into<StrOrString<'static>>,
         value: impl Into<StrOrString<'static>>,
     ) -> Self {
+        self.add_tag_as_ref(key, value);
+        self
+    }
+
+    pub fn add_tag_as_ref(
+        &mut self,
+        key: impl Into<StrOrString<'static>>,
+        value: impl Into<StrOrString<'static>>,
+    ) {
         if self.tags.is_none() {
             self.tags = Some(Vec::new());
         }
@@ -58,8 +161,6 @@ impl EventDurationTracker {
             key: key.into().to_string(),
             value: value.into().to_string(),
         });
-
-        self
     }
 }
 
@@ -69,12 +170,36 @@ impl Drop for EventDurationTracker {
             return;
         }
 
-        if !crate::TELEMETRY_INTERFACE.is_telemetry_set_up() {
+        let telemetry_interface = self.get_telemetry_interface().clone();
+        let finished = DateTimeAsMicroseconds::now();
+
+        // Durations are aggregated even if the event itself is not sampled
+        if let Some(event_name) = &self.event_name {
+            telemetry_interface.record_event_duration(
+                event_name.as_str(),
+                self.tags.as_deref(),
+                self.started,
+                finished,
+                self.fail_result.is_some(),
+            );
+        }
+
+        if !telemetry_interface.is_sampled(&self.my_telemetry) {
+            return;
+        }
+
+        if !telemetry_interface.is_telemetry_set_up() {
+            return;
+        }
+
+        // Events are written by the spawned task, trackers dropped outside of tokio runtime are lost
+        if tokio::runtime::Handle::try_current().is_err() {
             return;
         }
 
         let mut success = self.ok_result.take().map(|itm| itm.to_string());
         let fail = self.fail_result.take();
+        let tags = self.tags.take();
 
         if fail.is_some() {
             success = None;
@@ -88,16 +213,18 @@ impl Drop for EventDurationTracker {
                     let event = TelemetryEvent {
                         process_id: *process_id,
                         started: self.started.unix_microseconds,
-                        finished: DateTimeAsMicroseconds::now().unix_microseconds,
+                        finished: finished.unix_microseconds,
                         data: event_name.as_str().to_string(),
                         success,
                         fail,
-                        tags: None,
+                        tags,
+                        span_id: Some(self.span_id),
+                        parent_span_id: self.get_parent_span_id(*process_id),
                     };
+                    telemetry_interface.start_pending_write();
                     tokio::spawn(async move {
-                        crate::TELEMETRY_INTERFACE
-                            .write_telemetry_event(event)
-                            .await
+                        telemetry_interface.write_telemetry_event(event).await;
+                        telemetry_interface.finish_pending_write();
                     });
                 }
                 MyTelemetryContext::Multiple(ids) => {
@@ -107,19 +234,21 @@ impl Drop for EventDurationTracker {
                         let event = TelemetryEvent {
                             process_id: *process_id,
                             started: self.started.unix_microseconds,
-                            finished: DateTimeAsMicroseconds::now().unix_microseconds,
+                            finished: finished.unix_microseconds,
                             data: event_name.as_str().to_string(),
                             success: success.clone(),
                             fail: fail.clone(),
-                            tags: None,
+                            tags: tags.clone(),
+                            span_id: Some(self.span_id),
+                            parent_span_id: self.get_parent_span_id(*process_id),
                         };
 
                         events.push(event);
                     }
+                    telemetry_interface.start_pending_write();
                     tokio::spawn(async move {
-                        crate::TELEMETRY_INTERFACE
-                            .write_telemetry_events(events)
-                            .await
+                        telemetry_interface.write_telemetry_events(events).await;
+                        telemetry_interface.finish_pending_write();
                     });
                 }
 
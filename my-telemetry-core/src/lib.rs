@@ -1,16 +1,48 @@
+mod ambient_context;
 mod event_duration_tracker;
 mod my_telemetry_event;
 
+pub use ambient_context::*;
 pub use event_duration_tracker::*;
 mod ctx;
+mod duration_aggregator;
+pub use duration_aggregator::*;
 mod telemetry_collector;
 pub use ctx::*;
 pub use my_telemetry_event::*;
-pub use telemetry_collector::TelemetryCollector;
+pub use telemetry_collector::{
+    TelemetryCollector, TelemetryDroppedEvents, TelemetryOverflowPolicy, DEFAULT_COLLECTOR_CAPACITY,
+};
 mod telemetry_interface;
+#[cfg(feature = "log")]
+mod telemetry_log;
+#[cfg(feature = "log")]
+pub use telemetry_log::*;
+mod telemetry_logger;
 pub use telemetry_interface::*;
+pub use telemetry_logger::*;
+mod log_record;
+pub use log_record::*;
+mod metrics;
+pub use metrics::*;
+mod prometheus;
+pub use prometheus::*;
+mod propagation;
+pub use propagation::*;
+mod result_tracking;
+pub use result_tracking::*;
+mod sampling;
+pub use sampling::*;
+mod span_id;
+pub use span_id::*;
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "testing")]
+pub use testing::*;
+mod w3c_trace_context;
+pub use w3c_trace_context::*;
 
 lazy_static::lazy_static! {
-    pub static ref TELEMETRY_INTERFACE: TelemetryInterface =
-        TelemetryInterface::new();
+    pub static ref TELEMETRY_INTERFACE: std::sync::Arc<TelemetryInterface> =
+        std::sync::Arc::new(TelemetryInterface::new());
 }
@@ -0,0 +1,224 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use crate::{
+    AmbientTelemetryContext, EventDurationTracker, MyTelemetryContext, TelemetryEventTag,
+    TelemetryInterface,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TelemetryLogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warning,
+    Error,
+    FatalError,
+}
+
+impl TelemetryLogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TelemetryLogLevel::Trace => "Trace",
+            TelemetryLogLevel::Debug => "Debug",
+            TelemetryLogLevel::Info => "Info",
+            TelemetryLogLevel::Warning => "Warning",
+            TelemetryLogLevel::Error => "Error",
+            TelemetryLogLevel::FatalError => "FatalError",
+        }
+    }
+
+    pub fn parse(src: &str) -> Option<Self> {
+        match src {
+            "Trace" => Some(TelemetryLogLevel::Trace),
+            "Debug" => Some(TelemetryLogLevel::Debug),
+            "Info" => Some(TelemetryLogLevel::Info),
+            "Warning" => Some(TelemetryLogLevel::Warning),
+            "Error" => Some(TelemetryLogLevel::Error),
+            "FatalError" => Some(TelemetryLogLevel::FatalError),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryLogRecord {
+    pub process_id: i64,
+    pub span_id: Option<i64>,
+    pub timestamp: i64,
+    pub level: TelemetryLogLevel,
+    pub message: String,
+    pub tags: Option<Vec<TelemetryEventTag>>,
+}
+
+impl TelemetryInterface {
+    // Log records of the not sampled processes are skipped
+    pub async fn write_log_records(&self, log_records: Vec<TelemetryLogRecord>) {
+        let mut write_access = self.telemetry_collector.lock().await;
+        for log_record in log_records {
+            if self.is_process_sampled(log_record.process_id) {
+                write_access.write_log_record(log_record);
+            }
+        }
+    }
+
+    // Log record is written to every process of the context
+    pub fn log(
+        self: &Arc<Self>,
+        ctx: &MyTelemetryContext,
+        span_id: Option<i64>,
+        level: TelemetryLogLevel,
+        message: impl Into<String>,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        if !self.is_telemetry_set_up() {
+            return;
+        }
+
+        let process_ids = match ctx {
+            MyTelemetryContext::Single(process_id) => vec![*process_id],
+            MyTelemetryContext::Multiple(ids) => ids.clone(),
+            MyTelemetryContext::Empty => return,
+        };
+
+        // Records are written by the spawned task, records written outside of the tokio
+        // runtime are dropped and counted
+        if tokio::runtime::Handle::try_current().is_err() {
+            self.log_records_outside_of_runtime
+                .fetch_add(process_ids.len() as u64, Ordering::Relaxed);
+            return;
+        }
+
+        let timestamp = DateTimeAsMicroseconds::now().unix_microseconds;
+        let message = message.into();
+
+        let log_records: Vec<TelemetryLogRecord> = process_ids
+            .into_iter()
+            .filter(|process_id| self.is_process_sampled(*process_id))
+            .map(|process_id| TelemetryLogRecord {
+                process_id,
+                span_id,
+                timestamp,
+                level,
+                message: message.clone(),
+                tags: tags.clone(),
+            })
+            .collect();
+
+        if log_records.is_empty() {
+            return;
+        }
+
+        let telemetry_interface = self.clone();
+        telemetry_interface.start_pending_write();
+        tokio::spawn(async move {
+            telemetry_interface.write_log_records(log_records).await;
+            telemetry_interface.finish_pending_write();
+        });
+    }
+}
+
+impl TelemetryInterface {
+    pub fn get_log_records_outside_of_runtime(&self) -> u64 {
+        self.log_records_outside_of_runtime.load(Ordering::Relaxed)
+    }
+
+    // Records written outside of the ambient context become the log records of the new process
+    pub fn log_to_current_context(
+        self: &Arc<Self>,
+        process_name: &str,
+        level: TelemetryLogLevel,
+        message: impl Into<String>,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        if !self.is_telemetry_set_up() {
+            return;
+        }
+
+        // Records written in the span of the tracker belong to that span
+        let ambient = match AmbientTelemetryContext::current() {
+            Some(ambient) => ambient,
+            None => MyTelemetryContext::Single(self.generate_process_id(process_name)).into(),
+        };
+
+        self.log(&ambient.my_telemetry, ambient.span_id, level, message, tags);
+    }
+}
+
+impl MyTelemetryContext {
+    pub fn log(
+        &self,
+        level: TelemetryLogLevel,
+        message: impl Into<String>,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        crate::TELEMETRY_INTERFACE.log(self, None, level, message, tags);
+    }
+
+    pub fn log_info(&self, message: impl Into<String>) {
+        self.log(TelemetryLogLevel::Info, message, None);
+    }
+
+    pub fn log_warning(&self, message: impl Into<String>) {
+        self.log(TelemetryLogLevel::Warning, message, None);
+    }
+
+    pub fn log_error(&self, message: impl Into<String>) {
+        self.log(TelemetryLogLevel::Error, message, None);
+    }
+}
+
+impl EventDurationTracker {
+    // Log record is attached to the span of the tracker
+    pub fn log(
+        &self,
+        level: TelemetryLogLevel,
+        message: impl Into<String>,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        self.get_telemetry_interface().log(
+            &self.my_telemetry,
+            Some(self.span_id),
+            level,
+            message,
+            tags,
+        );
+    }
+
+    pub fn log_info(&self, message: impl Into<String>) {
+        self.log(TelemetryLogLevel::Info, message, None);
+    }
+
+    pub fn log_warning(&self, message: impl Into<String>) {
+        self.log(TelemetryLogLevel::Warning, message, None);
+    }
+
+    pub fn log_error(&self, message: impl Into<String>) {
+        self.log(TelemetryLogLevel::Error, message, None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_outside_of_runtime_is_dropped() {
+        let telemetry_interface = Arc::new(TelemetryInterface::new());
+        telemetry_interface
+            .writer_is_set
+            .store(true, Ordering::SeqCst);
+
+        telemetry_interface.log(
+            &MyTelemetryContext::Multiple(vec![1_700_000_000_000_000, 1_700_000_000_000_001]),
+            None,
+            TelemetryLogLevel::Info,
+            "message",
+            None,
+        );
+
+        assert_eq!(telemetry_interface.get_log_records_outside_of_runtime(), 2);
+        assert_eq!(telemetry_interface.get_pending_writes(), 0);
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::{HashMap, HashSet};
+
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use crate::TelemetryEventTag;
+
+pub const DEFAULT_MAX_METRIC_SERIES: usize = 10_000;
+
+pub const DEFAULT_HISTOGRAM_BOUNDS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryMetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+#[derive(Debug, Clone)]
+pub struct TelemetryHistogram {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub bounds: Vec<f64>,
+    // Has one more item than bounds: the last one counts values above the last bound
+    pub bucket_counts: Vec<u64>,
+}
+
+impl TelemetryHistogram {
+    pub fn new(bounds: Vec<f64>) -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: 0.0,
+            max: 0.0,
+            bucket_counts: vec![0; bounds.len() + 1],
+            bounds,
+        }
+    }
+
+    pub fn record(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+
+        self.count += 1;
+        self.sum += value;
+
+        let index = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+
+        self.bucket_counts[index] += 1;
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TelemetryMetricValue {
+    Counter(u64),
+    Gauge(f64),
+    Histogram(TelemetryHistogram),
+}
+
+impl TelemetryMetricValue {
+    pub fn get_kind(&self) -> TelemetryMetricKind {
+        match self {
+            TelemetryMetricValue::Counter(_) => TelemetryMetricKind::Counter,
+            TelemetryMetricValue::Gauge(_) => TelemetryMetricKind::Gauge,
+            TelemetryMetricValue::Histogram(_) => TelemetryMetricKind::Histogram,
+        }
+    }
+}
+
+// Values are cumulative since the metric is created
+#[derive(Debug, Clone)]
+pub struct TelemetryMetric {
+    pub name: String,
+    pub tags: Vec<TelemetryEventTag>,
+    pub value: TelemetryMetricValue,
+    pub started: i64,
+    pub updated: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TelemetryDroppedMetricUpdates {
+    // New series were not created because of the max_series limit
+    pub series_limit: u64,
+    // Name was already used by the metric of the other kind
+    pub kind_mismatch: u64,
+}
+
+// Every name has one kind. Updates of the other kind and the new series above the limit
+// are dropped and counted, the reason is printed once
+pub struct TelemetryMetricsCollector {
+    metrics: HashMap<String, TelemetryMetric>,
+    kinds: HashMap<String, TelemetryMetricKind>,
+    histogram_bounds: HashMap<String, Vec<f64>>,
+    max_series: Option<usize>,
+    dropped: TelemetryDroppedMetricUpdates,
+    // Names of the metrics which kind mismatch is printed
+    reported: HashSet<String>,
+    series_limit_reported: bool,
+}
+
+impl TelemetryMetricsCollector {
+    pub fn new() -> Self {
+        Self {
+            metrics: HashMap::new(),
+            kinds: HashMap::new(),
+            histogram_bounds: HashMap::new(),
+            max_series: Some(DEFAULT_MAX_METRIC_SERIES),
+            dropped: TelemetryDroppedMetricUpdates::default(),
+            reported: HashSet::new(),
+            series_limit_reported: false,
+        }
+    }
+
+    pub fn set_max_series(&mut self, max_series: Option<usize>) {
+        self.max_series = max_series;
+    }
+
+    pub fn get_max_series(&self) -> Option<usize> {
+        self.max_series
+    }
+
+    pub fn get_dropped_updates(&self) -> TelemetryDroppedMetricUpdates {
+        self.dropped
+    }
+
+    pub fn set_histogram_bounds(&mut self, name: impl Into<String>, mut bounds: Vec<f64>) {
+        bounds.sort_by(|a, b| a.total_cmp(b));
+        bounds.dedup();
+        self.histogram_bounds.insert(name.into(), bounds);
+    }
+
+    pub fn increment_counter(
+        &mut self,
+        name: &str,
+        value: u64,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        let metric = self.get_or_create(name, tags, TelemetryMetricKind::Counter, |_| {
+            TelemetryMetricValue::Counter(0)
+        });
+
+        if let Some(TelemetryMetricValue::Counter(counter)) = metric.map(|itm| &mut itm.value) {
+            *counter += value;
+        }
+    }
+
+    pub fn set_gauge(&mut self, name: &str, value: f64, tags: Option<Vec<TelemetryEventTag>>) {
+        let metric = self.get_or_create(name, tags, TelemetryMetricKind::Gauge, |_| {
+            TelemetryMetricValue::Gauge(0.0)
+        });
+
+        if let Some(TelemetryMetricValue::Gauge(gauge)) = metric.map(|itm| &mut itm.value) {
+            *gauge = value;
+        }
+    }
+
+    pub fn record_histogram(
+        &mut self,
+        name: &str,
+        value: f64,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        // Bounds are copied only when the series is created
+        let metric = self.get_or_create(name, tags, TelemetryMetricKind::Histogram, |collector| {
+            let bounds = match collector.histogram_bounds.get(name) {
+                Some(bounds) => bounds.clone(),
+                None => DEFAULT_HISTOGRAM_BOUNDS.to_vec(),
+            };
+
+            TelemetryMetricValue::Histogram(TelemetryHistogram::new(bounds))
+        });
+
+        if let Some(TelemetryMetricValue::Histogram(histogram)) = metric.map(|itm| &mut itm.value) {
+            histogram.record(value);
+        }
+    }
+
+    pub fn get_metrics(&self) -> Vec<TelemetryMetric> {
+        let mut result: Vec<TelemetryMetric> = self.metrics.values().cloned().collect();
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.metrics.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.metrics.is_empty()
+    }
+
+    fn get_or_create(
+        &mut self,
+        name: &str,
+        tags: Option<Vec<TelemetryEventTag>>,
+        kind: TelemetryMetricKind,
+        create_value: impl FnOnce(&Self) -> TelemetryMetricValue,
+    ) -> Option<&mut TelemetryMetric> {
+        if let Some(registered_kind) = self.kinds.get(name) {
+            if *registered_kind != kind {
+                self.dropped.kind_mismatch += 1;
+                report_once(&mut self.reported, name, || {
+                    format!(
+                        "Metric {} is {:?} and can not be updated as {:?}. Updates are dropped",
+                        name, registered_kind, kind
+                    )
+                });
+                return None;
+            }
+        }
+
+        let mut tags = tags.unwrap_or_default();
+        tags.sort_by(|a, b| a.key.cmp(&b.key));
+
+        let key = compile_metric_key(name, &tags);
+        let now = DateTimeAsMicroseconds::now().unix_microseconds;
+
+        if !self.metrics.contains_key(&key) {
+            if let Some(max_series) = self.max_series {
+                if self.metrics.len() >= max_series {
+                    self.dropped.series_limit += 1;
+                    if !self.series_limit_reported {
+                        self.series_limit_reported = true;
+                        println!(
+                            "Limit of {} metric series is reached. New series are dropped",
+                            max_series
+                        );
+                    }
+                    return None;
+                }
+            }
+
+            self.kinds.insert(name.to_string(), kind);
+
+            let value = create_value(self);
+            self.metrics.insert(
+                key.clone(),
+                TelemetryMetric {
+                    name: name.to_string(),
+                    tags,
+                    value,
+                    started: now,
+                    updated: now,
+                },
+            );
+        }
+
+        let metric = self.metrics.get_mut(&key)?;
+        metric.updated = now;
+        Some(metric)
+    }
+}
+
+impl Default for TelemetryMetricsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn report_once(reported: &mut HashSet<String>, name: &str, message: impl FnOnce() -> String) {
+    if reported.contains(name) {
+        return;
+    }
+
+    reported.insert(name.to_string());
+    println!("{}", message());
+}
+
+fn compile_metric_key(name: &str, tags: &[TelemetryEventTag]) -> String {
+    let mut result = name.to_string();
+
+    for tag in tags {
+        result.push('\u{0}');
+        result.push_str(&tag.key);
+        result.push('=');
+        result.push_str(&tag.value);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_other_kind_of_the_same_name_is_dropped() {
+        let mut collector = TelemetryMetricsCollector::new();
+
+        collector.increment_counter("requests", 1, None);
+        collector.set_gauge("requests", 5.0, None);
+        collector.record_histogram("requests", 5.0, None);
+
+        let metrics = collector.get_metrics();
+        assert_eq!(metrics.len(), 1);
+        assert!(matches!(metrics[0].value, TelemetryMetricValue::Counter(1)));
+        assert_eq!(collector.get_dropped_updates().kind_mismatch, 2);
+    }
+
+    #[test]
+    fn test_new_series_above_limit_are_dropped() {
+        let mut collector = TelemetryMetricsCollector::new();
+        collector.set_max_series(Some(2));
+
+        for client_id in ["client-1", "client-2", "client-3"] {
+            collector.increment_counter(
+                "requests",
+                1,
+                Some(vec![TelemetryEventTag {
+                    key: "client_id".to_string(),
+                    value: client_id.to_string(),
+                }]),
+            );
+        }
+
+        // Existing series are still updated
+        collector.increment_counter(
+            "requests",
+            1,
+            Some(vec![TelemetryEventTag {
+                key: "client_id".to_string(),
+                value: "client-1".to_string(),
+            }]),
+        );
+
+        let metrics = collector.get_metrics();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(collector.get_dropped_updates().series_limit, 1);
+
+        let client_1 = metrics
+            .iter()
+            .find(|itm| itm.tags[0].value == "client-1")
+            .unwrap();
+        assert!(matches!(client_1.value, TelemetryMetricValue::Counter(2)));
+    }
+}
@@ -1,5 +1,6 @@
 use rust_extensions::StrOrString;
 
+#[derive(Debug, Clone)]
 pub struct TelemetryEvent {
     pub process_id: i64,
     pub started: i64,
@@ -8,6 +9,8 @@ pub struct TelemetryEvent {
     pub success: Option<String>,
     pub fail: Option<String>,
     pub tags: Option<Vec<TelemetryEventTag>>,
+    pub span_id: Option<i64>,
+    pub parent_span_id: Option<i64>,
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,413 @@
+use crate::{TelemetryEventTag, TelemetryInterface, TelemetryMetric, TelemetryMetricValue};
+
+const COUNTER_SUFFIX: &str = "_total";
+
+struct PrometheusMetricFamily {
+    name: String,
+    metric_type: &'static str,
+    help: Option<String>,
+    lines: Vec<String>,
+}
+
+// Renders metrics in the Prometheus text exposition format (version 0.0.4)
+pub struct PrometheusTextBuilder {
+    families: Vec<PrometheusMetricFamily>,
+}
+
+impl PrometheusTextBuilder {
+    pub fn new() -> Self {
+        Self {
+            families: Vec::new(),
+        }
+    }
+
+    pub fn add_counter(&mut self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        let Some(family) = self.get_family(name, "counter", Some(help)) else {
+            return;
+        };
+        let line = compile_line(&family.name, "", labels, &[], value);
+        family.lines.push(line);
+    }
+
+    pub fn add_gauge(&mut self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        let Some(family) = self.get_family(name, "gauge", Some(help)) else {
+            return;
+        };
+        let line = compile_line(&family.name, "", labels, &[], value);
+        family.lines.push(line);
+    }
+
+    pub fn add_metric(&mut self, metric: &TelemetryMetric) {
+        let labels: Vec<(&str, &str)> = metric
+            .tags
+            .iter()
+            .map(|TelemetryEventTag { key, value }| (key.as_str(), value.as_str()))
+            .collect();
+
+        match &metric.value {
+            TelemetryMetricValue::Counter(value) => {
+                let Some(family) = self.get_family(&metric.name, "counter", None) else {
+                    return;
+                };
+                let line = compile_line(&family.name, "", &labels, &[], *value as f64);
+                family.lines.push(line);
+            }
+            TelemetryMetricValue::Gauge(value) => {
+                let Some(family) = self.get_family(&metric.name, "gauge", None) else {
+                    return;
+                };
+                let line = compile_line(&family.name, "", &labels, &[], *value);
+                family.lines.push(line);
+            }
+            TelemetryMetricValue::Histogram(histogram) => {
+                let Some(family) = self.get_family(&metric.name, "histogram", None) else {
+                    return;
+                };
+
+                let mut cumulative = 0;
+                for (bound, amount) in histogram.bounds.iter().zip(histogram.bucket_counts.iter()) {
+                    cumulative += amount;
+                    let le = format_value(*bound);
+                    let line = compile_line(
+                        &family.name,
+                        "_bucket",
+                        &labels,
+                        &[("le", le.as_str())],
+                        cumulative as f64,
+                    );
+                    family.lines.push(line);
+                }
+
+                let line = compile_line(
+                    &family.name,
+                    "_bucket",
+                    &labels,
+                    &[("le", "+Inf")],
+                    histogram.count as f64,
+                );
+                family.lines.push(line);
+
+                let line = compile_line(&family.name, "_sum", &labels, &[], histogram.sum);
+                family.lines.push(line);
+
+                let line =
+                    compile_line(&family.name, "_count", &labels, &[], histogram.count as f64);
+                family.lines.push(line);
+            }
+        }
+    }
+
+    pub fn add_metrics(&mut self, metrics: &[TelemetryMetric]) {
+        for metric in metrics {
+            self.add_metric(metric);
+        }
+    }
+
+    pub fn build(self) -> String {
+        let mut result = String::new();
+
+        for family in self.families {
+            if let Some(help) = family.help {
+                result.push_str(&format!("# HELP {} {}\n", family.name, escape_help(&help)));
+            }
+
+            result.push_str(&format!("# TYPE {} {}\n", family.name, family.metric_type));
+
+            for line in family.lines {
+                result.push_str(&line);
+                result.push('\n');
+            }
+        }
+
+        result
+    }
+
+    // Counter names get the _total suffix. Name which is already used by the other type
+    // is rejected, the exposition can not have two families with the same name
+    fn get_family(
+        &mut self,
+        name: &str,
+        metric_type: &'static str,
+        help: Option<&str>,
+    ) -> Option<&mut PrometheusMetricFamily> {
+        let mut name = sanitize_name(name);
+
+        if metric_type == "counter" && !name.ends_with(COUNTER_SUFFIX) {
+            name.push_str(COUNTER_SUFFIX);
+        }
+
+        let index = match self.families.iter().position(|itm| itm.name == name) {
+            Some(index) => {
+                if self.families[index].metric_type != metric_type {
+                    return None;
+                }
+
+                index
+            }
+            None => {
+                self.families.push(PrometheusMetricFamily {
+                    name,
+                    metric_type,
+                    help: help.map(|itm| itm.to_string()),
+                    lines: Vec::new(),
+                });
+                self.families.len() - 1
+            }
+        };
+
+        Some(&mut self.families[index])
+    }
+}
+
+impl Default for PrometheusTextBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TelemetryInterface {
+    pub async fn write_prometheus_metrics(&self, builder: &mut PrometheusTextBuilder) {
+        {
+            let read_access = self.telemetry_collector.lock().await;
+
+            builder.add_gauge(
+                "my_telemetry_collector_queued_events",
+                "Events which are waiting to be written",
+                &[],
+                read_access.len() as f64,
+            );
+
+            builder.add_gauge(
+                "my_telemetry_collector_queued_log_records",
+                "Log records which are waiting to be written",
+                &[],
+                read_access.get_log_records_len() as f64,
+            );
+
+            if let Some(capacity) = read_access.get_capacity() {
+                builder.add_gauge(
+                    "my_telemetry_collector_capacity",
+                    "Maximum amount of events the collector keeps",
+                    &[],
+                    capacity as f64,
+                );
+            }
+
+            let dropped = read_access.get_dropped_events();
+
+            for (reason, amount) in [
+                ("newest", dropped.newest_dropped),
+                ("oldest", dropped.oldest_dropped),
+                ("sampled_out", dropped.sampled_out),
+                ("not_sampled_process", dropped.not_sampled_process),
+                ("log_records", dropped.log_records_dropped),
+            ] {
+                builder.add_counter(
+                    "my_telemetry_collector_dropped_events",
+                    "Events which were dropped by the collector",
+                    &[("reason", reason)],
+                    amount as f64,
+                );
+            }
+        }
+
+        builder.add_gauge(
+            "my_telemetry_pending_writes",
+            "Events which are spawned to be written to the collector",
+            &[],
+            self.get_pending_writes() as f64,
+        );
+
+        builder.add_counter(
+            "my_telemetry_log_records_outside_of_runtime",
+            "Log records which were dropped because they were written outside of the tokio runtime",
+            &[],
+            self.get_log_records_outside_of_runtime() as f64,
+        );
+
+        builder.add_gauge(
+            "my_telemetry_writer_is_set",
+            "1 if the telemetry writer is started",
+            &[],
+            if self.is_telemetry_set_up() { 1.0 } else { 0.0 },
+        );
+
+        builder.add_gauge(
+            "my_telemetry_not_sampled_processes",
+            "Not sampled processes which are remembered",
+            &[],
+            self.not_sampled_processes.len() as f64,
+        );
+
+        builder.add_counter(
+            "my_telemetry_evicted_sampling_decisions",
+            "Not sampled decisions which were forgotten because of the capacity or the age",
+            &[],
+            self.get_evicted_sampling_decisions() as f64,
+        );
+
+        let dropped = self.get_dropped_metric_updates();
+
+        for (reason, amount) in [
+            ("series_limit", dropped.series_limit),
+            ("kind_mismatch", dropped.kind_mismatch),
+        ] {
+            builder.add_counter(
+                "my_telemetry_metrics_dropped_updates",
+                "Metric updates which were dropped",
+                &[("reason", reason)],
+                amount as f64,
+            );
+        }
+
+        builder.add_counter(
+            "my_telemetry_event_durations_dropped",
+            "Event durations which were dropped because of the series limit",
+            &[],
+            self.get_dropped_event_durations() as f64,
+        );
+
+        builder.add_metrics(self.get_all_metrics().as_slice());
+    }
+
+    pub async fn render_prometheus_metrics(&self) -> String {
+        let mut builder = PrometheusTextBuilder::new();
+        self.write_prometheus_metrics(&mut builder).await;
+        builder.build()
+    }
+}
+
+fn compile_line(
+    name: &str,
+    suffix: &str,
+    labels: &[(&str, &str)],
+    extra_labels: &[(&str, &str)],
+    value: f64,
+) -> String {
+    let mut result = format!("{}{}", name, suffix);
+
+    if !labels.is_empty() || !extra_labels.is_empty() {
+        result.push('{');
+
+        for (no, (key, value)) in labels.iter().chain(extra_labels.iter()).enumerate() {
+            if no > 0 {
+                result.push(',');
+            }
+
+            result.push_str(&sanitize_label_name(key));
+            result.push_str("=\"");
+            result.push_str(&escape_label_value(value));
+            result.push('"');
+        }
+
+        result.push('}');
+    }
+
+    result.push(' ');
+    result.push_str(&format_value(value));
+    result
+}
+
+// Metric names are [a-zA-Z_:][a-zA-Z0-9_:]*
+fn sanitize_name(name: &str) -> String {
+    sanitize(name, true)
+}
+
+// Label names are [a-zA-Z_][a-zA-Z0-9_]*, colons are not allowed
+fn sanitize_label_name(name: &str) -> String {
+    sanitize(name, false)
+}
+
+fn sanitize(name: &str, allow_colon: bool) -> String {
+    let mut result = String::with_capacity(name.len());
+
+    for (no, c) in name.chars().enumerate() {
+        if c.is_ascii_alphabetic()
+            || c == '_'
+            || (allow_colon && c == ':')
+            || (no > 0 && c.is_ascii_digit())
+        {
+            result.push(c);
+        } else if no == 0 && c.is_ascii_digit() {
+            result.push('_');
+            result.push(c);
+        } else {
+            result.push('_');
+        }
+    }
+
+    result
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_help(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+
+    if value.is_infinite() {
+        return if value > 0.0 { "+Inf" } else { "-Inf" }.to_string();
+    }
+
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_has_total_suffix() {
+        let mut builder = PrometheusTextBuilder::new();
+        builder.add_counter("requests", "Requests", &[("route", "/api")], 2.0);
+        builder.add_counter("responses_total", "Responses", &[], 1.0);
+
+        let result = builder.build();
+
+        assert!(result.contains("# TYPE requests_total counter\n"));
+        assert!(result.contains("requests_total{route=\"/api\"} 2\n"));
+        assert!(result.contains("# TYPE responses_total counter\n"));
+        assert!(!result.contains("responses_total_total"));
+    }
+
+    #[test]
+    fn test_same_name_of_other_type_is_rejected() {
+        let mut builder = PrometheusTextBuilder::new();
+        builder.add_gauge("queue", "Queue", &[], 1.0);
+        builder.add_counter("queue_total", "Queue", &[], 1.0);
+        builder.add_gauge("queue_total", "Queue", &[], 2.0);
+        builder.add_gauge("queue", "Queue", &[("kind", "other")], 3.0);
+
+        let result = builder.build();
+
+        assert_eq!(result.matches("# TYPE queue_total").count(), 1);
+        assert!(result.contains("queue_total 1\n"));
+        assert!(!result.contains("queue_total 2"));
+        assert!(result.contains("queue{kind=\"other\"} 3\n"));
+    }
+
+    #[test]
+    fn test_label_names_do_not_keep_colons() {
+        let mut builder = PrometheusTextBuilder::new();
+        builder.add_gauge(
+            "queue:depth",
+            "Queue",
+            &[("http:route", "/api"), ("2xx", "a:b")],
+            1.0,
+        );
+
+        let result = builder.build();
+
+        assert!(result.contains("queue:depth{http_route=\"/api\",_2xx=\"a:b\"} 1\n"));
+    }
+}
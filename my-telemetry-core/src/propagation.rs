@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use rust_extensions::StrOrString;
+
+use crate::{
+    AmbientTelemetryContext, EventDurationTracker, MyTelemetryContext, TelemetryInterface,
+    W3cTraceContext, TRACEPARENT_HEADER, TRACESTATE_HEADER,
+};
+
+// Header which carries MyTelemetryContext::as_string
+pub const PROCESS_ID_HEADER: &str = "process-id";
+
+// Context which came with the request from another service. Tracestate entries of the other
+// vendors are propagated further by the trackers of the request
+#[derive(Debug, Clone)]
+pub struct IncomingTelemetryContext {
+    pub my_telemetry: MyTelemetryContext,
+    pub parent_span_id: Option<i64>,
+    pub trace_state: Option<String>,
+    pub sampled: bool,
+}
+
+impl IncomingTelemetryContext {
+    // Process id header carries exact process ids, so it wins over traceparent.
+    // Sampled flag is taken from traceparent anyway
+    pub fn parse<'s>(get_header: impl Fn(&str) -> Option<&'s str>) -> Option<Self> {
+        let w3c_ctx = get_header(TRACEPARENT_HEADER).and_then(|traceparent| {
+            W3cTraceContext::parse(traceparent, get_header(TRACESTATE_HEADER)).ok()
+        });
+
+        let parent_span_id = w3c_ctx.as_ref().map(|itm| itm.parent_span_id as i64);
+        let trace_state = w3c_ctx
+            .as_ref()
+            .and_then(|itm| itm.get_foreign_trace_state());
+        let sampled = match &w3c_ctx {
+            Some(w3c_ctx) => w3c_ctx.sampled,
+            None => true,
+        };
+
+        if let Some(process_id) = get_header(PROCESS_ID_HEADER) {
+            if let Ok(my_telemetry) = MyTelemetryContext::parse_from_string(process_id) {
+                return Some(Self {
+                    my_telemetry,
+                    parent_span_id,
+                    trace_state,
+                    sampled,
+                });
+            }
+        }
+
+        let w3c_ctx = w3c_ctx?;
+
+        Some(Self {
+            my_telemetry: w3c_ctx.to_my_telemetry_context(),
+            parent_span_id,
+            trace_state,
+            sampled,
+        })
+    }
+
+    // Request without the context starts a new process. Not sampled flag of the request is
+    // remembered by the interface
+    pub fn start_event_tracking(
+        incoming: Option<Self>,
+        telemetry_interface: &Arc<TelemetryInterface>,
+        event_name: impl Into<StrOrString<'static>>,
+    ) -> EventDurationTracker {
+        match incoming {
+            Some(incoming) => {
+                if !incoming.sampled {
+                    telemetry_interface.mark_not_sampled(&incoming.my_telemetry);
+                }
+
+                let mut result =
+                    telemetry_interface.start_event_tracking(&incoming.my_telemetry, event_name);
+                if incoming.parent_span_id.is_some() {
+                    result.parent_span_id = incoming.parent_span_id;
+                }
+                result.trace_state = incoming.trace_state;
+                result
+            }
+            None => telemetry_interface.start_duration_tracking(event_name),
+        }
+    }
+}
+
+impl MyTelemetryContext {
+    pub fn get_propagation_headers(&self) -> Vec<(&'static str, String)> {
+        compile_propagation_headers(self, self.to_w3c_trace_context(), None)
+    }
+}
+
+impl EventDurationTracker {
+    // Span of the tracker becomes the parent span of the remote side
+    pub fn get_propagation_headers(&self) -> Vec<(&'static str, String)> {
+        compile_propagation_headers(
+            &self.my_telemetry,
+            self.to_w3c_trace_context(),
+            self.trace_state.as_deref(),
+        )
+    }
+}
+
+impl AmbientTelemetryContext {
+    // Span of the scope becomes the parent span of the remote side
+    pub fn get_propagation_headers(&self) -> Vec<(&'static str, String)> {
+        let mut w3c_ctx = self.my_telemetry.to_w3c_trace_context();
+        w3c_ctx.sampled = self.sampled;
+        if let Some(span_id) = self.span_id {
+            w3c_ctx.parent_span_id = span_id as u64;
+        }
+
+        compile_propagation_headers(&self.my_telemetry, w3c_ctx, self.trace_state.as_deref())
+    }
+}
+
+fn compile_propagation_headers(
+    my_telemetry: &MyTelemetryContext,
+    mut w3c_ctx: W3cTraceContext,
+    foreign_trace_state: Option<&str>,
+) -> Vec<(&'static str, String)> {
+    if let MyTelemetryContext::Empty = my_telemetry {
+        return vec![];
+    }
+
+    w3c_ctx.add_foreign_trace_state(foreign_trace_state);
+
+    let mut result = Vec::with_capacity(3);
+
+    result.push((PROCESS_ID_HEADER, my_telemetry.as_string()));
+    if let Ok(traceparent) = w3c_ctx.to_traceparent() {
+        result.push((TRACEPARENT_HEADER, traceparent));
+
+        if let Some(trace_state) = w3c_ctx.trace_state {
+            result.push((TRACESTATE_HEADER, trace_state));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_foreign_trace_state_is_propagated_by_tracker() {
+        let incoming = IncomingTelemetryContext::parse(|name| match name {
+            TRACEPARENT_HEADER => Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+            TRACESTATE_HEADER => Some("mytelemetry=1.2,congo=t61rcWkgMzE"),
+            _ => None,
+        });
+
+        let tracker = IncomingTelemetryContext::start_event_tracking(
+            incoming,
+            &crate::TELEMETRY_INTERFACE,
+            "request",
+        );
+        let child = tracker.start_child("child");
+
+        let headers = child.get_propagation_headers();
+        let trace_state = headers
+            .iter()
+            .find(|(name, _)| *name == TRACESTATE_HEADER)
+            .map(|(_, value)| value.as_str());
+
+        assert_eq!(trace_state, Some("mytelemetry=1.2,congo=t61rcWkgMzE"));
+    }
+}
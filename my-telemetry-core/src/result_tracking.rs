@@ -0,0 +1,244 @@
+use std::{fmt::Display, future::Future};
+
+use rust_extensions::StrOrString;
+
+use crate::{EventDurationTracker, MyTelemetryContext, MyTelemetryFutureExt};
+
+pub const ERROR_KIND_TAG: &str = "error_kind";
+pub const OK_RESULT: &str = "Ok";
+
+// Fail result is the Display value of the error by default.
+// Error kind is written as the error_kind tag if it is set
+pub struct TelemetryErrorFormatter<E> {
+    format_error: FormatError<E>,
+    get_error_kind: Option<FormatError<E>>,
+}
+
+type FormatError<E> = Box<dyn Fn(&E) -> String + Send + Sync>;
+
+impl<E: Display + 'static> TelemetryErrorFormatter<E> {
+    pub fn new() -> Self {
+        Self::from_fn(|err| err.to_string())
+    }
+}
+
+impl<E: std::fmt::Debug + 'static> TelemetryErrorFormatter<E> {
+    pub fn debug() -> Self {
+        Self::from_fn(|err| format!("{:?}", err))
+    }
+}
+
+impl<E> TelemetryErrorFormatter<E> {
+    pub fn from_fn(format_error: impl Fn(&E) -> String + Send + Sync + 'static) -> Self {
+        Self {
+            format_error: Box::new(format_error),
+            get_error_kind: None,
+        }
+    }
+
+    pub fn with_error_kind(
+        mut self,
+        get_error_kind: impl Fn(&E) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.get_error_kind = Some(Box::new(get_error_kind));
+        self
+    }
+}
+
+impl<E: Display + 'static> Default for TelemetryErrorFormatter<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventDurationTracker {
+    // Formatter is not created here, so errors which borrow data can be tracked too
+    pub fn track_result<T, E: Display>(&mut self, result: Result<T, E>) -> Result<T, E> {
+        self.set_result(&result, &|err| err.to_string(), None);
+        result
+    }
+
+    // Ok result keeps the fail result and the ok result which were set by the caller
+    pub fn track_result_with<T, E>(
+        &mut self,
+        result: Result<T, E>,
+        formatter: &TelemetryErrorFormatter<E>,
+    ) -> Result<T, E> {
+        self.set_result(
+            &result,
+            formatter.format_error.as_ref(),
+            formatter.get_error_kind.as_deref(),
+        );
+        result
+    }
+
+    fn set_result<T, E>(
+        &mut self,
+        result: &Result<T, E>,
+        format_error: &dyn Fn(&E) -> String,
+        get_error_kind: Option<&(dyn Fn(&E) -> String + Send + Sync)>,
+    ) {
+        match result {
+            Ok(_) => {
+                if self.fail_result.is_none() && self.ok_result.is_none() {
+                    self.set_ok_result(OK_RESULT);
+                }
+            }
+            Err(err) => {
+                self.set_fail_result(format_error(err));
+
+                if let Some(get_error_kind) = get_error_kind {
+                    self.add_tag_as_ref(ERROR_KIND_TAG, get_error_kind(err));
+                }
+            }
+        }
+    }
+}
+
+impl MyTelemetryContext {
+    // Future is executed in the span of the tracked event, so its events are the children
+    pub async fn track_async<T, E: Display>(
+        &self,
+        event_name: impl Into<StrOrString<'static>>,
+        future: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let mut tracker = self.start_event_tracking(event_name);
+        let result = future.with_tracker(&tracker).await;
+        tracker.track_result(result)
+    }
+
+    pub async fn track_async_with<T, E>(
+        &self,
+        event_name: impl Into<StrOrString<'static>>,
+        future: impl Future<Output = Result<T, E>>,
+        formatter: &TelemetryErrorFormatter<E>,
+    ) -> Result<T, E> {
+        let mut tracker = self.start_event_tracking(event_name);
+        let result = future.with_tracker(&tracker).await;
+        tracker.track_result_with(result, formatter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_tracker() -> EventDurationTracker {
+        MyTelemetryContext::Single(crate::generate_process_id()).start_event_tracking("event")
+    }
+
+    #[test]
+    fn test_ok_sets_ok_result() {
+        let mut tracker = create_tracker();
+
+        let result = tracker.track_result(Ok::<_, String>(42));
+
+        assert_eq!(result, Ok(42));
+        assert_eq!(tracker.ok_result.as_ref().unwrap().as_str(), OK_RESULT);
+        assert!(tracker.fail_result.is_none());
+    }
+
+    #[test]
+    fn test_ok_keeps_fail_result_set_by_caller() {
+        let mut tracker = create_tracker();
+        tracker.set_fail_result("Stock is low".to_string());
+
+        let _ = tracker.track_result(Ok::<_, String>(42));
+
+        assert_eq!(tracker.fail_result.as_deref(), Some("Stock is low"));
+        assert!(tracker.ok_result.is_none());
+    }
+
+    #[test]
+    fn test_err_sets_fail_result() {
+        let mut tracker = create_tracker();
+        tracker.set_ok_result("Found");
+
+        let result = tracker.track_result(Err::<i32, _>("Not found".to_string()));
+
+        assert_eq!(result, Err("Not found".to_string()));
+        assert_eq!(tracker.fail_result.as_deref(), Some("Not found"));
+        assert!(tracker.ok_result.is_none());
+        assert!(tracker.tags.is_none());
+    }
+
+    #[test]
+    fn test_err_with_error_kind_tag() {
+        let mut tracker = create_tracker();
+        let formatter = TelemetryErrorFormatter::<std::num::ParseIntError>::debug()
+            .with_error_kind(|err| format!("{:?}", err.kind()));
+
+        let result = tracker.track_result_with("forty-two".parse::<i32>(), &formatter);
+
+        assert!(result.is_err());
+        assert_eq!(
+            tracker.fail_result.as_deref(),
+            Some("ParseIntError { kind: InvalidDigit }")
+        );
+
+        let tags = tracker.tags.as_ref().unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].key, ERROR_KIND_TAG);
+        assert_eq!(tags[0].value, "InvalidDigit");
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_track_async_writes_result_and_children() {
+        use crate::{TelemetryEventMatcher, TelemetryTestHarness};
+
+        let harness = TelemetryTestHarness::install().await;
+        let process_id = crate::generate_process_id();
+        let ctx = MyTelemetryContext::Single(process_id);
+
+        let result = ctx
+            .track_async("get-order", async {
+                let _child = EventDurationTracker::new("load-order", None);
+                Ok::<_, String>(42)
+            })
+            .await;
+        assert_eq!(result, Ok(42));
+
+        // Closures can capture the state
+        let service = "orders".to_string();
+        let formatter =
+            TelemetryErrorFormatter::from_fn(move |err: &String| format!("{}: {}", service, err))
+                .with_error_kind(|err| err.split(':').next().unwrap_or_default().to_string());
+
+        let result = ctx
+            .track_async_with(
+                "delete-order",
+                async {
+                    let _child = EventDurationTracker::new("lock-order", None);
+                    Err::<i32, _>("Locked: order 42 is in progress".to_string())
+                },
+                &formatter,
+            )
+            .await;
+        assert!(result.is_err());
+
+        let parents = harness
+            .find(&TelemetryEventMatcher::event("get-order").with_success())
+            .await;
+        let children = harness
+            .find(&TelemetryEventMatcher::event("load-order").with_process_id(process_id))
+            .await;
+        assert_eq!(parents.len(), 1);
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].parent_span_id, parents[0].span_id);
+
+        let parents = harness
+            .find(
+                &TelemetryEventMatcher::event("delete-order")
+                    .with_fail_message("orders: Locked: order 42 is in progress")
+                    .with_tag(ERROR_KIND_TAG, "Locked"),
+            )
+            .await;
+        let children = harness
+            .find(&TelemetryEventMatcher::event("lock-order").with_process_id(process_id))
+            .await;
+        assert_eq!(parents.len(), 1);
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].parent_span_id, parents[0].span_id);
+    }
+}
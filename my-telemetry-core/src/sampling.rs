@@ -0,0 +1,322 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Mutex, RwLock,
+    },
+};
+
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use crate::{MyTelemetryContext, TelemetryInterface};
+
+const RATIO_PRECISION: u64 = 1_000_000;
+
+pub const NOT_SAMPLED_PROCESSES_CAPACITY: usize = 100_000;
+const NOT_SAMPLED_PROCESS_TTL_MICROSECONDS: i64 = 60 * 60 * 1_000_000;
+
+// Process id stays the same for not sampled process, so legacy services which use
+// as_string/parse_from_string get the same ids. The decision is remembered by the interface
+// and travels to other services with the traceparent sampled flag
+pub struct NotSampledProcesses {
+    ids: RwLock<NotSampledProcessesInner>,
+    amount: AtomicUsize,
+    evicted: AtomicU64,
+    capacity: usize,
+}
+
+struct NotSampledProcessesInner {
+    ids: HashSet<i64>,
+    added: VecDeque<(i64, i64)>,
+}
+
+impl NotSampledProcesses {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            ids: RwLock::new(NotSampledProcessesInner {
+                ids: HashSet::new(),
+                added: VecDeque::new(),
+            }),
+            amount: AtomicUsize::new(0),
+            evicted: AtomicU64::new(0),
+            capacity,
+        }
+    }
+
+    // The oldest decisions are forgotten after an hour or when there are too many of them.
+    // Events of the forgotten processes are written again, so they are counted
+    pub fn mark(&self, process_id: i64) {
+        let now = DateTimeAsMicroseconds::now().unix_microseconds;
+        let mut write_access = self.ids.write().unwrap();
+
+        if write_access.ids.insert(process_id) {
+            write_access.added.push_back((now, process_id));
+        }
+
+        while let Some((added, id)) = write_access.added.front().copied() {
+            if write_access.added.len() <= self.capacity
+                && now - added < NOT_SAMPLED_PROCESS_TTL_MICROSECONDS
+            {
+                break;
+            }
+
+            write_access.added.pop_front();
+            write_access.ids.remove(&id);
+            self.evicted.fetch_add(1, Ordering::Relaxed);
+        }
+
+        self.amount.store(write_access.ids.len(), Ordering::Relaxed);
+    }
+
+    // Nothing is locked while every process is sampled
+    pub fn is_sampled(&self, process_id: i64) -> bool {
+        if self.amount.load(Ordering::Relaxed) == 0 {
+            return true;
+        }
+
+        let read_access = self.ids.read().unwrap();
+        !read_access.ids.contains(&process_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.amount.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get_evicted(&self) -> u64 {
+        self.evicted.load(Ordering::Relaxed)
+    }
+}
+
+impl TelemetryInterface {
+    pub fn is_process_sampled(&self, process_id: i64) -> bool {
+        self.not_sampled_processes.is_sampled(process_id)
+    }
+
+    pub fn is_sampled(&self, ctx: &MyTelemetryContext) -> bool {
+        match ctx {
+            MyTelemetryContext::Empty => true,
+            MyTelemetryContext::Single(process_id) => self.is_process_sampled(*process_id),
+            MyTelemetryContext::Multiple(ids) => ids.iter().any(|id| self.is_process_sampled(*id)),
+        }
+    }
+
+    pub fn mark_not_sampled(&self, ctx: &MyTelemetryContext) {
+        match ctx {
+            MyTelemetryContext::Empty => {}
+            MyTelemetryContext::Single(process_id) => self.not_sampled_processes.mark(*process_id),
+            MyTelemetryContext::Multiple(ids) => {
+                for id in ids {
+                    self.not_sampled_processes.mark(*id);
+                }
+            }
+        }
+    }
+
+    // Not sampled decisions which were forgotten before the process has finished
+    pub fn get_evicted_sampling_decisions(&self) -> u64 {
+        self.not_sampled_processes.get_evicted()
+    }
+}
+
+pub fn get_sampling_hash(process_id: i64) -> u64 {
+    // splitmix64 finalizer gives uniform distribution for sequential ids
+    let mut result = (process_id as u64).wrapping_add(0x9E3779B97F4A7C15);
+    result = (result ^ (result >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    result = (result ^ (result >> 27)).wrapping_mul(0x94D049BB133111EB);
+    result ^ (result >> 31)
+}
+
+pub fn is_in_ratio(process_id: i64, ratio: f64) -> bool {
+    if ratio >= 1.0 {
+        return true;
+    }
+
+    if ratio <= 0.0 {
+        return false;
+    }
+
+    get_sampling_hash(process_id) % RATIO_PRECISION < (ratio * RATIO_PRECISION as f64) as u64
+}
+
+struct RateLimitWindow {
+    second: i64,
+    amount: u32,
+}
+
+pub struct HeadSampler {
+    ratio: f64,
+    rate_limits: HashMap<String, u32>,
+    windows: Mutex<HashMap<String, RateLimitWindow>>,
+}
+
+impl HeadSampler {
+    pub fn new() -> Self {
+        Self {
+            ratio: 1.0,
+            rate_limits: HashMap::new(),
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_ratio(mut self, ratio: f64) -> Self {
+        self.ratio = ratio;
+        self
+    }
+
+    pub fn with_rate_limit(mut self, event_name: impl Into<String>, per_second: u32) -> Self {
+        self.rate_limits.insert(event_name.into(), per_second);
+        self
+    }
+
+    pub fn should_sample(&self, process_id: i64, event_name: &str) -> bool {
+        if !is_in_ratio(process_id, self.ratio) {
+            return false;
+        }
+
+        let limit = match self.rate_limits.get(event_name) {
+            Some(limit) => *limit,
+            None => return true,
+        };
+
+        let second = DateTimeAsMicroseconds::now().unix_microseconds / 1_000_000;
+
+        let mut windows = self.windows.lock().unwrap();
+
+        let window = windows
+            .entry(event_name.to_string())
+            .or_insert(RateLimitWindow { second, amount: 0 });
+
+        if window.second != second {
+            window.second = second;
+            window.amount = 0;
+        }
+
+        if window.amount >= limit {
+            return false;
+        }
+
+        window.amount += 1;
+        true
+    }
+}
+
+impl Default for HeadSampler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Context without the interface uses the decisions of the default one
+impl MyTelemetryContext {
+    pub fn is_sampled(&self) -> bool {
+        crate::TELEMETRY_INTERFACE.is_sampled(self)
+    }
+
+    pub fn mark_not_sampled(&self) {
+        crate::TELEMETRY_INTERFACE.mark_not_sampled(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{IncomingTelemetryContext, TRACEPARENT_HEADER};
+
+    #[test]
+    fn test_not_sampled_process_keeps_its_id() {
+        let telemetry_interface = Arc::new(TelemetryInterface::new());
+        let ctx = MyTelemetryContext::Single(1_700_000_000_000_000);
+        let tracker = telemetry_interface.start_event_tracking(&ctx, "request");
+
+        assert!(telemetry_interface.is_sampled(&ctx));
+
+        telemetry_interface.mark_not_sampled(&ctx);
+
+        assert!(!telemetry_interface.is_sampled(&ctx));
+        assert_eq!(ctx.as_string(), "1700000000000000");
+        assert!(tracker
+            .to_w3c_trace_context()
+            .to_traceparent()
+            .unwrap()
+            .ends_with("-00"));
+
+        // Decisions of the interfaces do not leak to each other
+        assert!(TelemetryInterface::new().is_sampled(&ctx));
+    }
+
+    #[test]
+    fn test_not_sampled_flag_travels_with_traceparent() {
+        let telemetry_interface = Arc::new(TelemetryInterface::new());
+
+        let incoming = IncomingTelemetryContext::parse(|name| match name {
+            TRACEPARENT_HEADER => Some("00-000000000000000000060a24181e4000-00060a24181e4000-00"),
+            _ => None,
+        });
+
+        let tracker = IncomingTelemetryContext::start_event_tracking(
+            incoming,
+            &telemetry_interface,
+            "request",
+        );
+
+        assert_eq!(tracker.my_telemetry.as_string(), "1700000000000000");
+        assert!(!telemetry_interface.is_sampled(&tracker.my_telemetry));
+    }
+
+    #[test]
+    fn test_head_sampler_ratio() {
+        let head_sampler = HeadSampler::new().with_ratio(0.25);
+
+        let sampled = (1..=10_000)
+            .filter(|process_id| head_sampler.should_sample(*process_id, "event"))
+            .count();
+
+        assert!((2_200..=2_800).contains(&sampled), "sampled: {}", sampled);
+
+        // Decision depends only on the process id
+        for process_id in 1..=100 {
+            assert_eq!(
+                head_sampler.should_sample(process_id, "event"),
+                is_in_ratio(process_id, 0.25)
+            );
+        }
+    }
+
+    #[test]
+    fn test_head_sampler_rate_limit() {
+        let head_sampler = HeadSampler::new().with_rate_limit("health-check", 2);
+
+        let sampled = (1..=10)
+            .filter(|process_id| head_sampler.should_sample(*process_id, "health-check"))
+            .count();
+
+        // Window can be switched to the next second in the middle of the loop
+        assert!((2..=4).contains(&sampled), "sampled: {}", sampled);
+        assert!(head_sampler.should_sample(11, "other"));
+    }
+
+    #[test]
+    fn test_oldest_decisions_are_evicted() {
+        let not_sampled_processes = NotSampledProcesses::new(2);
+
+        for process_id in 1..=3 {
+            not_sampled_processes.mark(process_id);
+        }
+
+        assert!(not_sampled_processes.is_sampled(1));
+        assert!(!not_sampled_processes.is_sampled(2));
+        assert!(!not_sampled_processes.is_sampled(3));
+        assert_eq!(not_sampled_processes.len(), 2);
+        assert_eq!(not_sampled_processes.get_evicted(), 1);
+
+        // Marking the same process again is not a new decision
+        not_sampled_processes.mark(3);
+        assert_eq!(not_sampled_processes.get_evicted(), 1);
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::atomic::{AtomicI64, Ordering},
+};
+
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+static LAST_TIMESTAMP: AtomicI64 = AtomicI64::new(0);
+
+lazy_static::lazy_static! {
+    static ref SPAN_ID_SALT: u64 = {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        hasher.write_i64(DateTimeAsMicroseconds::now().unix_microseconds);
+        hasher.finish()
+    };
+}
+
+// Process ids are the current time in microseconds and are unique within the process
+pub fn generate_process_id() -> i64 {
+    let now = DateTimeAsMicroseconds::now().unix_microseconds;
+    let mut last = LAST_TIMESTAMP.load(Ordering::Relaxed);
+
+    loop {
+        let next = if now > last { now } else { last + 1 };
+
+        match LAST_TIMESTAMP.compare_exchange_weak(last, next, Ordering::Relaxed, Ordering::Relaxed)
+        {
+            Ok(_) => return next,
+            Err(actual) => last = actual,
+        }
+    }
+}
+
+// Unique id is mixed with the random salt of the process,
+// so spans of different services which are started at the same time do not collide
+pub fn generate_span_id() -> i64 {
+    let mut result = generate_process_id() as u64 ^ *SPAN_ID_SALT;
+
+    // splitmix64 finalizer
+    result = (result ^ (result >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    result = (result ^ (result >> 27)).wrapping_mul(0x94D049BB133111EB);
+    result ^= result >> 31;
+
+    match (result & i64::MAX as u64) as i64 {
+        0 => 1,
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_span_ids_are_unique_and_positive() {
+        let mut span_ids = HashSet::new();
+
+        for _ in 0..10_000 {
+            let span_id = super::generate_span_id();
+            assert!(span_id > 0);
+            assert!(span_ids.insert(span_id));
+        }
+    }
+
+    #[test]
+    fn test_process_ids_are_increasing_timestamps() {
+        let first = super::generate_process_id();
+        let second = super::generate_process_id();
+
+        assert!(second > first);
+        assert!(first >= 1_600_000_000_000_000);
+    }
+}
@@ -1,44 +1,304 @@
-use crate::TelemetryEvent;
+use std::collections::VecDeque;
+
+use crate::{TelemetryEvent, TelemetryLogRecord};
+
+#[derive(Debug, Clone, Copy)]
+pub enum TelemetryOverflowPolicy {
+    DropNewest,
+    DropOldest,
+    // When collector is full only every N-th new event is kept instead of the oldest one.
+    // Kept event drops the oldest one, other new events are sampled out
+    Sample(usize),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TelemetryDroppedEvents {
+    pub newest_dropped: u64,
+    pub oldest_dropped: u64,
+    pub sampled_out: u64,
+    pub not_sampled_process: u64,
+    // Log records are not counted in total
+    pub log_records_dropped: u64,
+}
+
+impl TelemetryDroppedEvents {
+    pub fn total(&self) -> u64 {
+        self.newest_dropped + self.oldest_dropped + self.sampled_out + self.not_sampled_process
+    }
+}
+
+// Collector is bounded by default, so the events do not grow memory while the exporter is down
+pub const DEFAULT_COLLECTOR_CAPACITY: usize = 100_000;
 
 pub struct TelemetryCollector {
-    events_to_publish: Option<Vec<TelemetryEvent>>,
+    events_to_publish: Option<VecDeque<TelemetryEvent>>,
+    log_records_to_publish: Option<VecDeque<TelemetryLogRecord>>,
+    capacity: Option<usize>,
+    overflow_policy: TelemetryOverflowPolicy,
+    overflow_no: usize,
+    dropped: TelemetryDroppedEvents,
 }
 
 impl TelemetryCollector {
     pub fn new() -> Self {
         Self {
             events_to_publish: None,
+            log_records_to_publish: None,
+            capacity: Some(DEFAULT_COLLECTOR_CAPACITY),
+            overflow_policy: TelemetryOverflowPolicy::DropNewest,
+            overflow_no: 0,
+            dropped: TelemetryDroppedEvents::default(),
         }
     }
 
-    pub fn write(&mut self, event: TelemetryEvent) {
-        if self.events_to_publish.is_none() {
-            self.events_to_publish = Some(Vec::new());
+    pub fn new_with_capacity(capacity: usize, overflow_policy: TelemetryOverflowPolicy) -> Self {
+        let mut result = Self::new();
+        result.set_capacity(Some(capacity), overflow_policy);
+        result
+    }
+
+    pub fn set_capacity(
+        &mut self,
+        capacity: Option<usize>,
+        overflow_policy: TelemetryOverflowPolicy,
+    ) {
+        self.capacity = capacity;
+        self.overflow_policy = overflow_policy;
+        self.overflow_no = 0;
+
+        let capacity = match capacity {
+            Some(capacity) => capacity,
+            None => return,
+        };
+
+        if let Some(events) = self.events_to_publish.as_mut() {
+            while events.len() > capacity {
+                events.pop_front();
+                self.dropped.oldest_dropped += 1;
+            }
         }
 
-        self.events_to_publish.as_mut().unwrap().push(event);
+        if let Some(log_records) = self.log_records_to_publish.as_mut() {
+            while log_records.len() > capacity {
+                log_records.pop_front();
+                self.dropped.log_records_dropped += 1;
+            }
+        }
     }
 
-    pub fn write_events(&mut self, events: Vec<TelemetryEvent>) {
-        if self.events_to_publish.is_none() {
-            self.events_to_publish = Some(Vec::new());
+    pub fn get_capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
+    pub fn get_dropped_events(&self) -> TelemetryDroppedEvents {
+        self.dropped
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.events_to_publish {
+            Some(events) => events.len(),
+            None => 0,
         }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Interface knows which processes are not sampled, the collector only counts their events
+    pub fn drop_not_sampled_process_event(&mut self) {
+        self.dropped.not_sampled_process += 1;
+    }
+
+    pub fn write(&mut self, event: TelemetryEvent) {
+        let capacity = self.capacity;
+        let keeper = self.events_to_publish.get_or_insert_with(VecDeque::new);
 
-        let keeper = self.events_to_publish.as_mut().unwrap();
+        let capacity = match capacity {
+            Some(capacity) => capacity,
+            None => {
+                keeper.push_back(event);
+                return;
+            }
+        };
+
+        if keeper.len() < capacity {
+            keeper.push_back(event);
+            return;
+        }
+
+        match self.overflow_policy {
+            TelemetryOverflowPolicy::DropNewest => {
+                self.dropped.newest_dropped += 1;
+            }
+            TelemetryOverflowPolicy::DropOldest => {
+                if keeper.pop_front().is_some() {
+                    self.dropped.oldest_dropped += 1;
+                }
+                if capacity > 0 {
+                    keeper.push_back(event);
+                } else {
+                    self.dropped.newest_dropped += 1;
+                }
+            }
+            TelemetryOverflowPolicy::Sample(keep_every) => {
+                // Every keep_every-th overflowed event is kept
+                self.overflow_no += 1;
+                if keep_every > 0 && capacity > 0 && self.overflow_no >= keep_every {
+                    self.overflow_no = 0;
+                    keeper.pop_front();
+                    keeper.push_back(event);
+                    self.dropped.oldest_dropped += 1;
+                } else {
+                    self.dropped.sampled_out += 1;
+                }
+            }
+        }
+    }
+
+    pub fn write_events(&mut self, events: Vec<TelemetryEvent>) {
         for event in events {
-            keeper.push(event);
+            self.write(event);
         }
     }
 
     pub fn get_events(&mut self) -> Option<Vec<TelemetryEvent>> {
-        if self.events_to_publish.is_none() {
+        self.overflow_no = 0;
+        let events = self.events_to_publish.take()?;
+
+        if events.is_empty() {
             return None;
         }
 
-        self.events_to_publish.take()
+        Some(events.into())
     }
 
     pub fn clear_events(&mut self) {
         self.events_to_publish = None;
+        self.log_records_to_publish = None;
+    }
+
+    // Log records share the capacity of the events, the oldest ones are dropped first
+    pub fn write_log_record(&mut self, log_record: TelemetryLogRecord) {
+        let keeper = self
+            .log_records_to_publish
+            .get_or_insert_with(VecDeque::new);
+
+        if let Some(capacity) = self.capacity {
+            if capacity == 0 {
+                self.dropped.log_records_dropped += 1;
+                return;
+            }
+
+            while keeper.len() >= capacity {
+                keeper.pop_front();
+                self.dropped.log_records_dropped += 1;
+            }
+        }
+
+        keeper.push_back(log_record);
+    }
+
+    pub fn write_log_records(&mut self, log_records: Vec<TelemetryLogRecord>) {
+        for log_record in log_records {
+            self.write_log_record(log_record);
+        }
+    }
+
+    pub fn get_log_records(&mut self) -> Option<Vec<TelemetryLogRecord>> {
+        let log_records = self.log_records_to_publish.take()?;
+
+        if log_records.is_empty() {
+            return None;
+        }
+
+        Some(log_records.into())
+    }
+
+    pub fn get_log_records_len(&self) -> usize {
+        match &self.log_records_to_publish {
+            Some(log_records) => log_records.len(),
+            None => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_event(process_id: i64) -> TelemetryEvent {
+        TelemetryEvent {
+            process_id,
+            started: 0,
+            finished: 0,
+            data: "test".to_string(),
+            success: None,
+            fail: None,
+            tags: None,
+            span_id: None,
+            parent_span_id: None,
+        }
+    }
+
+    fn get_process_ids(collector: &mut TelemetryCollector) -> Vec<i64> {
+        collector
+            .get_events()
+            .unwrap()
+            .into_iter()
+            .map(|itm| itm.process_id)
+            .collect()
+    }
+
+    #[test]
+    fn test_collector_is_bounded_by_default() {
+        let collector = TelemetryCollector::new();
+        assert_eq!(collector.get_capacity(), Some(DEFAULT_COLLECTOR_CAPACITY));
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let mut collector =
+            TelemetryCollector::new_with_capacity(2, TelemetryOverflowPolicy::DropNewest);
+
+        collector.write_events(vec![create_event(1), create_event(2), create_event(3)]);
+
+        assert_eq!(collector.get_dropped_events().newest_dropped, 1);
+        assert_eq!(collector.get_dropped_events().total(), 1);
+        assert_eq!(get_process_ids(&mut collector), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let mut collector =
+            TelemetryCollector::new_with_capacity(2, TelemetryOverflowPolicy::DropOldest);
+
+        collector.write_events(vec![create_event(1), create_event(2), create_event(3)]);
+
+        assert_eq!(collector.get_dropped_events().oldest_dropped, 1);
+        assert_eq!(collector.get_dropped_events().total(), 1);
+        assert_eq!(get_process_ids(&mut collector), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_sample_counts_only_dropped_events() {
+        let mut collector =
+            TelemetryCollector::new_with_capacity(2, TelemetryOverflowPolicy::Sample(2));
+
+        collector.write_events(vec![
+            create_event(1),
+            create_event(2),
+            create_event(3),
+            create_event(4),
+            create_event(5),
+        ]);
+
+        let dropped = collector.get_dropped_events();
+
+        // 3 and 5 are sampled out, 4 is kept instead of 1
+        assert_eq!(dropped.sampled_out, 2);
+        assert_eq!(dropped.oldest_dropped, 1);
+        assert_eq!(dropped.total(), 3);
+        assert_eq!(get_process_ids(&mut collector), vec![2, 4]);
     }
 }
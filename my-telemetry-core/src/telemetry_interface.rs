@@ -1,15 +1,27 @@
-use std::sync::atomic::AtomicBool;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize},
+    Arc,
+};
 
-use rust_extensions::date_time::DateTimeAsMicroseconds;
+use rust_extensions::{date_time::DateTimeAsMicroseconds, StrOrString};
 use tokio::sync::Mutex;
 
 use crate::{
-    my_telemetry_event::TelemetryEventTag, MyTelemetryContext, TelemetryCollector, TelemetryEvent,
+    my_telemetry_event::TelemetryEventTag, EventDurationAggregator, EventDurationStats,
+    EventDurationTracker, HeadSampler, MyTelemetryContext, NotSampledProcesses, TelemetryCollector,
+    TelemetryDroppedEvents, TelemetryDroppedMetricUpdates, TelemetryEvent, TelemetryMetric,
+    TelemetryMetricsCollector, TelemetryOverflowPolicy, NOT_SAMPLED_PROCESSES_CAPACITY,
 };
 
 pub struct TelemetryInterface {
     pub telemetry_collector: Mutex<TelemetryCollector>,
     pub writer_is_set: AtomicBool,
+    pending_writes: AtomicUsize,
+    pub(crate) log_records_outside_of_runtime: AtomicU64,
+    head_sampler: std::sync::Mutex<Option<Arc<HeadSampler>>>,
+    pub(crate) not_sampled_processes: NotSampledProcesses,
+    pub metrics_collector: std::sync::Mutex<TelemetryMetricsCollector>,
+    pub duration_aggregator: std::sync::Mutex<EventDurationAggregator>,
 }
 
 impl TelemetryInterface {
@@ -17,7 +29,192 @@ impl TelemetryInterface {
         Self {
             telemetry_collector: Mutex::new(TelemetryCollector::new()),
             writer_is_set: AtomicBool::new(false),
+            pending_writes: AtomicUsize::new(0),
+            log_records_outside_of_runtime: AtomicU64::new(0),
+            head_sampler: std::sync::Mutex::new(None),
+            not_sampled_processes: NotSampledProcesses::new(NOT_SAMPLED_PROCESSES_CAPACITY),
+            metrics_collector: std::sync::Mutex::new(TelemetryMetricsCollector::new()),
+            duration_aggregator: std::sync::Mutex::new(EventDurationAggregator::new()),
+        }
+    }
+
+    pub fn new_with_capacity(capacity: usize, overflow_policy: TelemetryOverflowPolicy) -> Self {
+        Self {
+            telemetry_collector: Mutex::new(TelemetryCollector::new_with_capacity(
+                capacity,
+                overflow_policy,
+            )),
+            writer_is_set: AtomicBool::new(false),
+            pending_writes: AtomicUsize::new(0),
+            log_records_outside_of_runtime: AtomicU64::new(0),
+            head_sampler: std::sync::Mutex::new(None),
+            not_sampled_processes: NotSampledProcesses::new(NOT_SAMPLED_PROCESSES_CAPACITY),
+            metrics_collector: std::sync::Mutex::new(TelemetryMetricsCollector::new()),
+            duration_aggregator: std::sync::Mutex::new(EventDurationAggregator::new()),
+        }
+    }
+
+    pub async fn set_capacity(
+        &self,
+        capacity: Option<usize>,
+        overflow_policy: TelemetryOverflowPolicy,
+    ) {
+        let mut write_access = self.telemetry_collector.lock().await;
+        write_access.set_capacity(capacity, overflow_policy);
+    }
+
+    pub async fn get_dropped_events(&self) -> TelemetryDroppedEvents {
+        let read_access = self.telemetry_collector.lock().await;
+        read_access.get_dropped_events()
+    }
+
+    pub fn set_head_sampler(&self, head_sampler: HeadSampler) {
+        *self.head_sampler.lock().unwrap() = Some(Arc::new(head_sampler));
+    }
+
+    pub fn remove_head_sampler(&self) {
+        *self.head_sampler.lock().unwrap() = None;
+    }
+
+    pub fn generate_process_id(&self, process_name: &str) -> i64 {
+        let process_id = crate::generate_process_id();
+
+        let head_sampler = self.head_sampler.lock().unwrap().clone();
+
+        if let Some(head_sampler) = head_sampler {
+            if !head_sampler.should_sample(process_id, process_name) {
+                self.not_sampled_processes.mark(process_id);
+            }
+        }
+
+        process_id
+    }
+
+    pub fn increment_counter(
+        &self,
+        name: impl Into<StrOrString<'static>>,
+        value: u64,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        let mut write_access = self.metrics_collector.lock().unwrap();
+        write_access.increment_counter(name.into().as_str(), value, tags);
+    }
+
+    pub fn set_gauge(
+        &self,
+        name: impl Into<StrOrString<'static>>,
+        value: f64,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        let mut write_access = self.metrics_collector.lock().unwrap();
+        write_access.set_gauge(name.into().as_str(), value, tags);
+    }
+
+    pub fn record_histogram(
+        &self,
+        name: impl Into<StrOrString<'static>>,
+        value: f64,
+        tags: Option<Vec<TelemetryEventTag>>,
+    ) {
+        let mut write_access = self.metrics_collector.lock().unwrap();
+        write_access.record_histogram(name.into().as_str(), value, tags);
+    }
+
+    pub fn set_histogram_bounds(&self, name: impl Into<String>, bounds: Vec<f64>) {
+        let mut write_access = self.metrics_collector.lock().unwrap();
+        write_access.set_histogram_bounds(name, bounds);
+    }
+
+    // Series above the limit are dropped. None removes the limit
+    pub fn set_max_metric_series(&self, max_series: Option<usize>) {
+        let mut write_access = self.metrics_collector.lock().unwrap();
+        write_access.set_max_series(max_series);
+    }
+
+    pub fn get_dropped_metric_updates(&self) -> TelemetryDroppedMetricUpdates {
+        let read_access = self.metrics_collector.lock().unwrap();
+        read_access.get_dropped_updates()
+    }
+
+    pub fn get_metrics(&self) -> Vec<TelemetryMetric> {
+        let read_access = self.metrics_collector.lock().unwrap();
+        read_access.get_metrics()
+    }
+
+    pub fn set_event_duration_tag_keys(&self, tag_keys: Vec<String>) {
+        let mut write_access = self.duration_aggregator.lock().unwrap();
+        write_access.set_tag_keys(tag_keys);
+    }
+
+    // Series above the limit are dropped. None removes the limit
+    pub fn set_max_event_duration_series(&self, max_series: Option<usize>) {
+        let mut write_access = self.duration_aggregator.lock().unwrap();
+        write_access.set_max_series(max_series);
+    }
+
+    pub fn get_dropped_event_durations(&self) -> u64 {
+        let read_access = self.duration_aggregator.lock().unwrap();
+        read_access.get_dropped()
+    }
+
+    pub fn record_event_duration(
+        &self,
+        event_name: &str,
+        tags: Option<&[TelemetryEventTag]>,
+        started: DateTimeAsMicroseconds,
+        finished: DateTimeAsMicroseconds,
+        is_fail: bool,
+    ) {
+        // Trackers are dropped on the hot path, so there is no lock without the writer.
+        // Durations are not aggregated until the writer is started
+        if !self.is_telemetry_set_up() {
+            return;
         }
+
+        let mut write_access = self.duration_aggregator.lock().unwrap();
+        write_access.record(
+            event_name,
+            tags,
+            finished.unix_microseconds - started.unix_microseconds,
+            is_fail,
+            finished.unix_microseconds,
+        );
+    }
+
+    pub fn get_event_durations(&self) -> Vec<EventDurationStats> {
+        let read_access = self.duration_aggregator.lock().unwrap();
+        read_access.get_stats()
+    }
+
+    pub fn clear_event_durations(&self) {
+        let mut write_access = self.duration_aggregator.lock().unwrap();
+        write_access.clear();
+    }
+
+    // Metrics together with the event durations converted to metrics
+    pub fn get_all_metrics(&self) -> Vec<TelemetryMetric> {
+        let mut result = self.get_metrics();
+
+        let read_access = self.duration_aggregator.lock().unwrap();
+        result.extend(read_access.get_metrics());
+
+        result
+    }
+
+    // Events which are spawned to be written to the collector but are not there yet
+    pub fn get_pending_writes(&self) -> usize {
+        self.pending_writes
+            .load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    pub fn start_pending_write(&self) {
+        self.pending_writes
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn finish_pending_write(&self) {
+        self.pending_writes
+            .fetch_sub(1, std::sync::atomic::Ordering::SeqCst);
     }
 
     pub fn is_telemetry_set_up(&self) -> bool {
@@ -25,6 +222,22 @@ impl TelemetryInterface {
             .load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    pub fn start_duration_tracking(
+        self: &Arc<Self>,
+        process_name: impl Into<StrOrString<'static>>,
+    ) -> EventDurationTracker {
+        EventDurationTracker::start_process(process_name, None, Some(self.clone()))
+    }
+
+    pub fn start_event_tracking(
+        self: &Arc<Self>,
+        ctx: &MyTelemetryContext,
+        event_name: impl Into<StrOrString<'static>>,
+    ) -> EventDurationTracker {
+        ctx.start_event_tracking(event_name)
+            .with_telemetry_interface(self.clone())
+    }
+
     pub async fn write_success(
         &self,
         ctx: &MyTelemetryContext,
@@ -47,6 +260,8 @@ impl TelemetryInterface {
                     success: Some(success),
                     fail: None,
                     tags,
+                    span_id: None,
+                    parent_span_id: None,
                 };
                 let mut write_access = self.telemetry_collector.lock().await;
                 write_access.write(event)
@@ -62,6 +277,8 @@ impl TelemetryInterface {
                         success: Some(success.to_string()),
                         fail: None,
                         tags: tags.clone(),
+                        span_id: None,
+                        parent_span_id: None,
                     };
 
                     events.push(event);
@@ -75,6 +292,8 @@ impl TelemetryInterface {
                     success: Some(success),
                     fail: None,
                     tags,
+                    span_id: None,
+                    parent_span_id: None,
                 };
 
                 events.push(event);
@@ -109,6 +328,8 @@ impl TelemetryInterface {
                     success: None,
                     fail: Some(fail),
                     tags,
+                    span_id: None,
+                    parent_span_id: None,
                 };
                 let mut write_access = self.telemetry_collector.lock().await;
                 write_access.write(event)
@@ -124,6 +345,8 @@ impl TelemetryInterface {
                         success: None,
                         fail: Some(fail.clone()),
                         tags: tags.clone(),
+                        span_id: None,
+                        parent_span_id: None,
                     };
 
                     events.push(event);
@@ -137,12 +360,13 @@ impl TelemetryInterface {
                     success: None,
                     fail: Some(fail),
                     tags,
+                    span_id: None,
+                    parent_span_id: None,
                 };
 
                 events.push(event);
 
-                let mut write_access = self.telemetry_collector.lock().await;
-                write_access.write_events(events)
+                self.write_telemetry_events(events).await;
             }
             MyTelemetryContext::Empty => {}
         }
@@ -150,13 +374,22 @@ impl TelemetryInterface {
 
     pub async fn write_telemetry_event(&self, event: TelemetryEvent) {
         let mut write_access = self.telemetry_collector.lock().await;
+        if !self.is_process_sampled(event.process_id) {
+            write_access.drop_not_sampled_process_event();
+            return;
+        }
+
         write_access.write(event)
     }
 
     pub async fn write_telemetry_events(&self, events: Vec<TelemetryEvent>) {
         let mut write_access = self.telemetry_collector.lock().await;
-
         for event in events {
+            if !self.is_process_sampled(event.process_id) {
+                write_access.drop_not_sampled_process_event();
+                continue;
+            }
+
             write_access.write(event);
         }
     }
@@ -191,3 +424,25 @@ impl MyTelemetryCompiler {
         return MyTelemetryContext::Multiple(self.items);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_durations_are_recorded_only_with_writer() {
+        let telemetry_interface = TelemetryInterface::new();
+        let started = DateTimeAsMicroseconds::new(1_700_000_000_000_000);
+        let finished = DateTimeAsMicroseconds::new(1_700_000_000_000_100);
+
+        telemetry_interface.record_event_duration("event", None, started, finished, false);
+        assert!(telemetry_interface.get_event_durations().is_empty());
+
+        telemetry_interface
+            .writer_is_set
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+
+        telemetry_interface.record_event_duration("event", None, started, finished, false);
+        assert_eq!(telemetry_interface.get_event_durations().len(), 1);
+    }
+}
@@ -0,0 +1,268 @@
+use std::sync::Arc;
+
+use log::{kv::VisitSource, Level, LevelFilter, Log, Metadata, Record};
+
+use crate::{TelemetryEventTag, TelemetryInterface, TelemetryLogLevel};
+
+// Transports log while they export telemetry, so their records would be exported forever
+const DEFAULT_IGNORED_TARGETS: [&str; 6] =
+    ["h2", "hyper", "tonic", "tower", "rustls", "my_telemetry"];
+
+// log::Log which writes records as telemetry log records of the ambient MyTelemetryContext and
+// passes every record to the inner logger
+pub struct MyTelemetryLog {
+    inner: Box<dyn Log>,
+    inner_level: LevelFilter,
+    level: LevelFilter,
+    telemetry_interface: Option<Arc<TelemetryInterface>>,
+    ignored_targets: Vec<String>,
+}
+
+impl MyTelemetryLog {
+    pub fn new(inner: Box<dyn Log>, level: LevelFilter) -> Self {
+        Self {
+            inner,
+            inner_level: level,
+            level,
+            telemetry_interface: None,
+            ignored_targets: DEFAULT_IGNORED_TARGETS
+                .iter()
+                .map(|itm| itm.to_string())
+                .collect(),
+        }
+    }
+
+    pub fn with_telemetry_interface(
+        mut self,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        self.telemetry_interface = Some(telemetry_interface);
+        self
+    }
+
+    // Level of the records which are passed to the inner logger. Same as the telemetry level by default
+    pub fn with_inner_level(mut self, inner_level: LevelFilter) -> Self {
+        self.inner_level = inner_level;
+        self
+    }
+
+    pub fn with_ignored_target(mut self, target_prefix: impl Into<String>) -> Self {
+        self.ignored_targets.push(target_prefix.into());
+        self
+    }
+
+    pub fn init(self) -> Result<(), log::SetLoggerError> {
+        let level = std::cmp::max(self.level, self.inner_level);
+        log::set_boxed_logger(Box::new(self))?;
+        log::set_max_level(level);
+        Ok(())
+    }
+
+    fn get_telemetry_interface(&self) -> &Arc<TelemetryInterface> {
+        match &self.telemetry_interface {
+            Some(telemetry_interface) => telemetry_interface,
+            None => &crate::TELEMETRY_INTERFACE,
+        }
+    }
+
+    fn is_telemetry_enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.level() > self.level {
+            return false;
+        }
+
+        let target = metadata.target();
+
+        !self
+            .ignored_targets
+            .iter()
+            .any(|itm| target.starts_with(itm.as_str()))
+    }
+
+    fn is_inner_enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.inner_level && self.inner.enabled(metadata)
+    }
+}
+
+impl Log for MyTelemetryLog {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.is_telemetry_enabled(metadata) || self.is_inner_enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.is_inner_enabled(record.metadata()) {
+            self.inner.log(record);
+        }
+
+        if !self.is_telemetry_enabled(record.metadata()) {
+            return;
+        }
+
+        let telemetry_interface = self.get_telemetry_interface();
+
+        if !telemetry_interface.is_telemetry_set_up() {
+            return;
+        }
+
+        let mut tags = TagsVisitor {
+            tags: vec![TelemetryEventTag {
+                key: "target".to_string(),
+                value: record.target().to_string(),
+            }],
+        };
+
+        let _ = record.key_values().visit(&mut tags);
+
+        telemetry_interface.log_to_current_context(
+            record.target(),
+            get_log_level(record.level()),
+            record.args().to_string(),
+            Some(tags.tags),
+        );
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+struct TagsVisitor {
+    tags: Vec<TelemetryEventTag>,
+}
+
+impl<'kvs> VisitSource<'kvs> for TagsVisitor {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        self.tags.push(TelemetryEventTag {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+
+        Ok(())
+    }
+}
+
+fn get_log_level(level: Level) -> TelemetryLogLevel {
+    match level {
+        Level::Error => TelemetryLogLevel::Error,
+        Level::Warn => TelemetryLogLevel::Warning,
+        Level::Info => TelemetryLogLevel::Info,
+        Level::Debug => TelemetryLogLevel::Debug,
+        Level::Trace => TelemetryLogLevel::Trace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+
+    use super::MyTelemetryLog;
+
+    struct CountingLog {
+        logged: Arc<AtomicUsize>,
+    }
+
+    impl Log for CountingLog {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, _record: &Record) {
+            self.logged.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn log(logger: &MyTelemetryLog, level: Level, target: &str) {
+        logger.log(
+            &Record::builder()
+                .args(format_args!("test"))
+                .level(level)
+                .target(target)
+                .build(),
+        );
+    }
+
+    #[test]
+    fn test_records_are_passed_to_inner_logger() {
+        let logged = Arc::new(AtomicUsize::new(0));
+        let logger = MyTelemetryLog::new(
+            Box::new(CountingLog {
+                logged: logged.clone(),
+            }),
+            LevelFilter::Warn,
+        )
+        .with_inner_level(LevelFilter::Info);
+
+        log(&logger, Level::Warn, "app");
+        log(&logger, Level::Info, "app");
+        log(&logger, Level::Debug, "app");
+        // Ignored targets are skipped by the telemetry only
+        log(&logger, Level::Warn, "hyper::client");
+
+        assert_eq!(logged.load(Ordering::SeqCst), 3);
+    }
+
+    #[cfg(feature = "testing")]
+    #[tokio::test]
+    async fn test_record_becomes_telemetry_log_record() {
+        use crate::{MyTelemetryContext, TelemetryLogLevel, TelemetryTestHarness};
+
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let logger = MyTelemetryLog::new(
+            Box::new(CountingLog {
+                logged: Arc::new(AtomicUsize::new(0)),
+            }),
+            LevelFilter::Info,
+        )
+        .with_telemetry_interface(harness.get_telemetry_interface().clone());
+
+        let tracker = MyTelemetryContext::Single(1_700_000_000_000_000)
+            .start_event_tracking("request")
+            .with_telemetry_interface(harness.get_telemetry_interface().clone());
+
+        tracker.sync_scope(|| {
+            let key_values = [("client_id", "client"), ("attempt", "2")];
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("payment is declined"))
+                    .level(Level::Warn)
+                    .target("payments")
+                    .key_values(&key_values)
+                    .build(),
+            );
+        });
+
+        let log_records = harness.get_log_records().await;
+        assert_eq!(log_records.len(), 1);
+
+        let log_record = &log_records[0];
+        assert_eq!(log_record.process_id, 1_700_000_000_000_000);
+        assert_eq!(log_record.span_id, Some(tracker.span_id));
+        assert_eq!(log_record.level, TelemetryLogLevel::Warning);
+        assert_eq!(log_record.message, "payment is declined");
+
+        let tags: Vec<(&str, &str)> = log_record
+            .tags
+            .iter()
+            .flatten()
+            .map(|itm| (itm.key.as_str(), itm.value.as_str()))
+            .collect();
+        assert_eq!(
+            tags,
+            vec![
+                ("target", "payments"),
+                ("client_id", "client"),
+                ("attempt", "2")
+            ]
+        );
+    }
+}
@@ -0,0 +1,224 @@
+use std::{collections::HashMap, sync::Arc};
+
+use rust_extensions::Logger;
+
+use crate::{TelemetryEventTag, TelemetryInterface, TelemetryLogLevel};
+
+// Delegates to the inner logger and forwards records of min_level and above as telemetry
+// log records of the ambient MyTelemetryContext. Records written outside of the context
+// become the log records of the new process
+pub struct TelemetryLogger {
+    inner: Arc<dyn Logger + Send + Sync + 'static>,
+    telemetry_interface: Option<Arc<TelemetryInterface>>,
+    min_level: TelemetryLogLevel,
+}
+
+impl TelemetryLogger {
+    pub fn new(inner: Arc<dyn Logger + Send + Sync + 'static>) -> Self {
+        Self {
+            inner,
+            telemetry_interface: None,
+            min_level: TelemetryLogLevel::Warning,
+        }
+    }
+
+    pub fn with_telemetry_interface(
+        mut self,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        self.telemetry_interface = Some(telemetry_interface);
+        self
+    }
+
+    pub fn with_min_level(mut self, min_level: TelemetryLogLevel) -> Self {
+        self.min_level = min_level;
+        self
+    }
+
+    fn forward(
+        &self,
+        level: TelemetryLogLevel,
+        process: &str,
+        message: &str,
+        ctx: Option<&HashMap<String, String>>,
+    ) {
+        if level < self.min_level {
+            return;
+        }
+
+        let telemetry_interface = match &self.telemetry_interface {
+            Some(telemetry_interface) => telemetry_interface,
+            None => &crate::TELEMETRY_INTERFACE,
+        };
+
+        if !telemetry_interface.is_telemetry_set_up() {
+            return;
+        }
+
+        let mut tags = vec![TelemetryEventTag {
+            key: "process".to_string(),
+            value: process.to_string(),
+        }];
+
+        if let Some(ctx) = ctx {
+            let mut ctx: Vec<_> = ctx.iter().collect();
+            ctx.sort();
+
+            for (key, value) in ctx {
+                tags.push(TelemetryEventTag {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+
+        telemetry_interface.log_to_current_context(process, level, message, Some(tags));
+    }
+}
+
+impl Logger for TelemetryLogger {
+    fn write_info(&self, process: String, message: String, ctx: Option<HashMap<String, String>>) {
+        self.forward(TelemetryLogLevel::Info, &process, &message, ctx.as_ref());
+        self.inner.write_info(process, message, ctx);
+    }
+
+    fn write_warning(
+        &self,
+        process: String,
+        message: String,
+        ctx: Option<HashMap<String, String>>,
+    ) {
+        self.forward(TelemetryLogLevel::Warning, &process, &message, ctx.as_ref());
+        self.inner.write_warning(process, message, ctx);
+    }
+
+    fn write_error(&self, process: String, message: String, ctx: Option<HashMap<String, String>>) {
+        self.forward(TelemetryLogLevel::Error, &process, &message, ctx.as_ref());
+        self.inner.write_error(process, message, ctx);
+    }
+
+    fn write_fatal_error(
+        &self,
+        process: String,
+        message: String,
+        ctx: Option<HashMap<String, String>>,
+    ) {
+        self.forward(
+            TelemetryLogLevel::FatalError,
+            &process,
+            &message,
+            ctx.as_ref(),
+        );
+        self.inner.write_fatal_error(process, message, ctx);
+    }
+
+    fn write_debug_info(
+        &self,
+        process: String,
+        message: String,
+        ctx: Option<HashMap<String, String>>,
+    ) {
+        self.forward(TelemetryLogLevel::Debug, &process, &message, ctx.as_ref());
+        self.inner.write_debug_info(process, message, ctx);
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{MyTelemetryContext, TelemetryTestHarness};
+
+    #[derive(Default)]
+    struct TestLogger {
+        messages: Mutex<Vec<String>>,
+    }
+
+    impl TestLogger {
+        fn add(&self, message: String) {
+            self.messages.lock().unwrap().push(message);
+        }
+    }
+
+    impl Logger for TestLogger {
+        fn write_info(&self, _: String, message: String, _: Option<HashMap<String, String>>) {
+            self.add(message);
+        }
+        fn write_warning(&self, _: String, message: String, _: Option<HashMap<String, String>>) {
+            self.add(message);
+        }
+        fn write_error(&self, _: String, message: String, _: Option<HashMap<String, String>>) {
+            self.add(message);
+        }
+        fn write_fatal_error(
+            &self,
+            _: String,
+            message: String,
+            _: Option<HashMap<String, String>>,
+        ) {
+            self.add(message);
+        }
+        fn write_debug_info(&self, _: String, message: String, _: Option<HashMap<String, String>>) {
+            self.add(message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_are_written_to_the_current_context() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let inner = Arc::new(TestLogger::default());
+        let logger = TelemetryLogger::new(inner.clone())
+            .with_telemetry_interface(harness.get_telemetry_interface().clone());
+
+        let tracker = MyTelemetryContext::Single(1_700_000_000_000_000)
+            .start_event_tracking("request")
+            .with_telemetry_interface(harness.get_telemetry_interface().clone());
+
+        tracker
+            .scope(async {
+                let ctx = HashMap::from([("client_id".to_string(), "client".to_string())]);
+                logger.write_error("payments".to_string(), "error".to_string(), Some(ctx));
+                logger.write_info("payments".to_string(), "info".to_string(), None);
+            })
+            .await;
+
+        let log_records = harness.get_log_records().await;
+        assert_eq!(log_records.len(), 1);
+
+        let log_record = &log_records[0];
+        assert_eq!(log_record.process_id, 1_700_000_000_000_000);
+        assert_eq!(log_record.span_id, Some(tracker.span_id));
+        assert_eq!(log_record.level, TelemetryLogLevel::Error);
+        assert_eq!(log_record.message, "error");
+
+        let tags: Vec<(&str, &str)> = log_record
+            .tags
+            .iter()
+            .flatten()
+            .map(|itm| (itm.key.as_str(), itm.value.as_str()))
+            .collect();
+        assert_eq!(tags, vec![("process", "payments"), ("client_id", "client")]);
+
+        // Inner logger gets every record
+        assert_eq!(*inner.messages.lock().unwrap(), vec!["error", "info"]);
+    }
+
+    #[tokio::test]
+    async fn test_record_outside_of_context_starts_new_process() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let logger = TelemetryLogger::new(Arc::new(TestLogger::default()))
+            .with_telemetry_interface(harness.get_telemetry_interface().clone())
+            .with_min_level(TelemetryLogLevel::Info);
+
+        logger.write_info("startup".to_string(), "started".to_string(), None);
+        logger.write_fatal_error("startup".to_string(), "failed".to_string(), None);
+
+        let log_records = harness.get_log_records().await;
+        assert_eq!(log_records.len(), 2);
+        assert_eq!(log_records[0].level, TelemetryLogLevel::Info);
+        assert_eq!(log_records[1].level, TelemetryLogLevel::FatalError);
+        assert_eq!(log_records[0].span_id, None);
+        assert_ne!(log_records[0].process_id, log_records[1].process_id);
+    }
+}
@@ -0,0 +1,387 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::{TelemetryEvent, TelemetryInterface, TelemetryLogRecord};
+
+lazy_static::lazy_static! {
+    static ref HARNESS_LOCK: Mutex<()> = Mutex::new(());
+}
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Captures events written to TELEMETRY_INTERFACE. Only one harness of the global interface
+// can be alive at a time, so tests which use it are executed one by one even if they are run
+// in parallel. Tests which use isolated harness are not blocked
+pub struct TelemetryTestHarness {
+    _lock: Option<MutexGuard<'static, ()>>,
+    telemetry_interface: Arc<TelemetryInterface>,
+    captured: Mutex<Vec<TelemetryEvent>>,
+    captured_log_records: Mutex<Vec<TelemetryLogRecord>>,
+}
+
+impl TelemetryTestHarness {
+    pub async fn install() -> Self {
+        let lock = HARNESS_LOCK.lock().await;
+        Self::create(Some(lock), crate::TELEMETRY_INTERFACE.clone()).await
+    }
+
+    pub async fn install_isolated() -> Self {
+        Self::create(None, Arc::new(TelemetryInterface::new())).await
+    }
+
+    // Events left by the previous harness are cleared here, after their pending writes are done
+    async fn create(
+        lock: Option<MutexGuard<'static, ()>>,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        wait_for_pending_writes(&telemetry_interface).await;
+
+        telemetry_interface
+            .telemetry_collector
+            .lock()
+            .await
+            .clear_events();
+
+        telemetry_interface
+            .writer_is_set
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+
+        Self {
+            _lock: lock,
+            telemetry_interface,
+            captured: Mutex::new(Vec::new()),
+            captured_log_records: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn get_telemetry_interface(&self) -> &Arc<TelemetryInterface> {
+        &self.telemetry_interface
+    }
+
+    pub async fn wait_for_pending_events(&self) {
+        wait_for_pending_writes(&self.telemetry_interface).await;
+    }
+
+    pub async fn get_events(&self) -> Vec<TelemetryEvent> {
+        self.wait_for_pending_events().await;
+
+        let events = self
+            .telemetry_interface
+            .telemetry_collector
+            .lock()
+            .await
+            .get_events();
+
+        let mut captured = self.captured.lock().await;
+
+        if let Some(events) = events {
+            captured.extend(events);
+        }
+
+        captured.clone()
+    }
+
+    pub async fn get_log_records(&self) -> Vec<TelemetryLogRecord> {
+        self.wait_for_pending_events().await;
+
+        let log_records = self
+            .telemetry_interface
+            .telemetry_collector
+            .lock()
+            .await
+            .get_log_records();
+
+        let mut captured = self.captured_log_records.lock().await;
+
+        if let Some(log_records) = log_records {
+            captured.extend(log_records);
+        }
+
+        captured.clone()
+    }
+
+    pub async fn find(&self, matcher: &TelemetryEventMatcher) -> Vec<TelemetryEvent> {
+        self.get_events()
+            .await
+            .into_iter()
+            .filter(|itm| matcher.matches(itm))
+            .collect()
+    }
+
+    pub async fn assert_recorded(&self, matcher: &TelemetryEventMatcher) {
+        let events = self.get_events().await;
+
+        if events.iter().any(|itm| matcher.matches(itm)) {
+            return;
+        }
+
+        panic!(
+            "Telemetry event {:?} was not recorded. Recorded events: {:#?}",
+            matcher, events
+        );
+    }
+
+    pub async fn assert_not_recorded(&self, matcher: &TelemetryEventMatcher) {
+        let events = self.find(matcher).await;
+
+        if events.is_empty() {
+            return;
+        }
+
+        panic!(
+            "Telemetry event {:?} was not expected. Found: {:#?}",
+            matcher, events
+        );
+    }
+
+    pub async fn clear(&self) {
+        self.get_events().await;
+        self.get_log_records().await;
+        self.captured.lock().await.clear();
+        self.captured_log_records.lock().await.clear();
+    }
+}
+
+impl Drop for TelemetryTestHarness {
+    fn drop(&mut self) {
+        self.telemetry_interface
+            .writer_is_set
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+async fn wait_for_pending_writes(telemetry_interface: &TelemetryInterface) {
+    let wait = async {
+        while telemetry_interface.get_pending_writes() > 0 {
+            tokio::task::yield_now().await;
+        }
+    };
+
+    if tokio::time::timeout(WAIT_TIMEOUT, wait).await.is_err() {
+        panic!(
+            "Pending telemetry events were not written in {:?}",
+            WAIT_TIMEOUT
+        );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ExpectedResult {
+    Success,
+    Fail,
+    FailWithMessage(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TelemetryEventMatcher {
+    pub event_name: Option<String>,
+    pub process_id: Option<i64>,
+    pub tags: Vec<(String, String)>,
+    pub result: Option<ExpectedResult>,
+}
+
+impl TelemetryEventMatcher {
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    pub fn event(event_name: impl Into<String>) -> Self {
+        Self {
+            event_name: Some(event_name.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_process_id(mut self, process_id: i64) -> Self {
+        self.process_id = Some(process_id);
+        self
+    }
+
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn with_success(mut self) -> Self {
+        self.result = Some(ExpectedResult::Success);
+        self
+    }
+
+    pub fn with_fail(mut self) -> Self {
+        self.result = Some(ExpectedResult::Fail);
+        self
+    }
+
+    pub fn with_fail_message(mut self, message: impl Into<String>) -> Self {
+        self.result = Some(ExpectedResult::FailWithMessage(message.into()));
+        self
+    }
+
+    pub fn matches(&self, event: &TelemetryEvent) -> bool {
+        if let Some(event_name) = &self.event_name {
+            if &event.data != event_name {
+                return false;
+            }
+        }
+
+        if let Some(process_id) = self.process_id {
+            if event.process_id != process_id {
+                return false;
+            }
+        }
+
+        for (key, value) in &self.tags {
+            let found = match &event.tags {
+                Some(tags) => tags
+                    .iter()
+                    .any(|tag| &tag.key == key && &tag.value == value),
+                None => false,
+            };
+
+            if !found {
+                return false;
+            }
+        }
+
+        match &self.result {
+            Some(ExpectedResult::Success) => event.fail.is_none(),
+            Some(ExpectedResult::Fail) => event.fail.is_some(),
+            Some(ExpectedResult::FailWithMessage(message)) => {
+                event.fail.as_deref() == Some(message.as_str())
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MyTelemetryContext, TelemetryLogLevel};
+
+    fn create_event(event_name: &str, fail: Option<&str>) -> TelemetryEvent {
+        TelemetryEvent {
+            process_id: 1_700_000_000_000_000,
+            started: 1_700_000_000_000_000,
+            finished: 1_700_000_000_000_100,
+            data: event_name.to_string(),
+            success: None,
+            fail: fail.map(|itm| itm.to_string()),
+            tags: Some(vec![crate::TelemetryEventTag {
+                key: "client_id".to_string(),
+                value: "client".to_string(),
+            }]),
+            span_id: None,
+            parent_span_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assert_recorded() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+
+        let process_id = 1_700_000_000_000_000;
+
+        {
+            let mut tracker = MyTelemetryContext::Single(process_id)
+                .start_event_tracking("tracked")
+                .with_telemetry_interface(harness.get_telemetry_interface().clone())
+                .add_tag("client_id", "client");
+            tracker.set_fail_result("error".to_string());
+        }
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("tracked")
+                    .with_process_id(process_id)
+                    .with_tag("client_id", "client")
+                    .with_fail_message("error"),
+            )
+            .await;
+
+        harness
+            .assert_not_recorded(&TelemetryEventMatcher::event("tracked").with_success())
+            .await;
+
+        harness
+            .assert_not_recorded(&TelemetryEventMatcher::event("other"))
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "was not recorded")]
+    async fn test_assert_recorded_panics_if_not_found() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+
+        harness
+            .get_telemetry_interface()
+            .write_telemetry_event(create_event("recorded", None))
+            .await;
+
+        harness
+            .assert_recorded(&TelemetryEventMatcher::event("recorded").with_fail())
+            .await;
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "was not expected")]
+    async fn test_assert_not_recorded_panics_if_found() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+
+        harness
+            .get_telemetry_interface()
+            .write_telemetry_event(create_event("recorded", Some("error")))
+            .await;
+
+        harness
+            .assert_not_recorded(&TelemetryEventMatcher::event("recorded").with_fail())
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_events_are_kept_between_reads_until_clear() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let telemetry_interface = harness.get_telemetry_interface().clone();
+
+        telemetry_interface
+            .write_telemetry_event(create_event("first", None))
+            .await;
+        assert_eq!(harness.get_events().await.len(), 1);
+
+        telemetry_interface
+            .write_telemetry_event(create_event("second", None))
+            .await;
+        assert_eq!(harness.get_events().await.len(), 2);
+
+        telemetry_interface.log(
+            &MyTelemetryContext::Single(1_700_000_000_000_000),
+            None,
+            TelemetryLogLevel::Error,
+            "message",
+            None,
+        );
+        assert_eq!(harness.get_log_records().await.len(), 1);
+
+        harness.clear().await;
+        assert!(harness.get_events().await.is_empty());
+        assert!(harness.get_log_records().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_events_of_previous_harness_are_cleared() {
+        {
+            let harness = TelemetryTestHarness::install().await;
+            harness
+                .get_telemetry_interface()
+                .write_telemetry_event(create_event("previous", None))
+                .await;
+        }
+
+        let harness = TelemetryTestHarness::install().await;
+
+        harness
+            .assert_not_recorded(&TelemetryEventMatcher::event("previous"))
+            .await;
+    }
+}
@@ -0,0 +1,375 @@
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+use crate::MyTelemetryContext;
+
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+pub const TRACESTATE_HEADER: &str = "tracestate";
+
+const TRACESTATE_KEY: &str = "mytelemetry";
+const SUPPORTED_VERSION: u8 = 0;
+// W3C limit of the tracestate list members
+const MAX_TRACESTATE_MEMBERS: usize = 32;
+const SAMPLED_FLAG: u8 = 0x01;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct W3cTraceContext {
+    pub trace_id: u128,
+    pub parent_span_id: u64,
+    pub sampled: bool,
+    pub trace_state: Option<String>,
+}
+
+impl W3cTraceContext {
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Result<Self, String> {
+        let mut parts = traceparent.trim().split('-');
+
+        let version = parse_hex_field(parts.next(), 2, "version")? as u8;
+        if version == 0xff {
+            return Err("Invalid traceparent version ff".to_string());
+        }
+
+        let trace_id = parse_hex_field(parts.next(), 32, "trace-id")?;
+        let parent_span_id = parse_hex_field(parts.next(), 16, "parent-id")? as u64;
+        check_ids(trace_id, parent_span_id)?;
+
+        let flags = parse_hex_field(parts.next(), 2, "trace-flags")? as u8;
+
+        // Future versions are allowed to append fields, version 00 is not
+        if version == SUPPORTED_VERSION && parts.next().is_some() {
+            return Err("traceparent version 00 has unexpected extra fields".to_string());
+        }
+
+        let trace_state = tracestate
+            .map(|itm| itm.trim())
+            .filter(|itm| !itm.is_empty())
+            .map(|itm| itm.to_string());
+
+        Ok(Self {
+            trace_id,
+            parent_span_id,
+            sampled: flags & SAMPLED_FLAG == SAMPLED_FLAG,
+            trace_state,
+        })
+    }
+
+    pub fn from_my_telemetry_context(ctx: &MyTelemetryContext) -> Self {
+        match ctx {
+            MyTelemetryContext::Single(process_id) => Self::from_process_id(*process_id, None),
+            MyTelemetryContext::Multiple(ids) => match ids.first() {
+                Some(first_id) => {
+                    Self::from_process_id(*first_id, Some(compile_trace_state_value(ids)))
+                }
+                None => {
+                    Self::from_process_id(DateTimeAsMicroseconds::now().unix_microseconds, None)
+                }
+            },
+            MyTelemetryContext::Empty => {
+                Self::from_process_id(DateTimeAsMicroseconds::now().unix_microseconds, None)
+            }
+        }
+    }
+
+    fn from_process_id(process_id: i64, process_ids: Option<String>) -> Self {
+        Self {
+            trace_id: process_id_to_trace_id(process_id),
+            parent_span_id: process_id as u64,
+            sampled: crate::TELEMETRY_INTERFACE.is_process_sampled(process_id),
+            trace_state: process_ids.map(|ids| format!("{}={}", TRACESTATE_KEY, ids)),
+        }
+    }
+
+    // Process id 0 gives all zeros trace-id which is not valid traceparent
+    pub fn to_traceparent(&self) -> Result<String, String> {
+        check_ids(self.trace_id, self.parent_span_id)?;
+
+        let flags = if self.sampled { SAMPLED_FLAG } else { 0 };
+        Ok(format!(
+            "{:02x}-{:032x}-{:016x}-{:02x}",
+            SUPPORTED_VERSION, self.trace_id, self.parent_span_id, flags
+        ))
+    }
+
+    pub fn get_process_ids(&self) -> Option<Vec<i64>> {
+        let trace_state = self.trace_state.as_ref()?;
+
+        for member in trace_state.split(',') {
+            let Some((key, value)) = member.trim().split_once('=') else {
+                continue;
+            };
+
+            if key != TRACESTATE_KEY {
+                continue;
+            }
+
+            let mut ids = Vec::new();
+            for id in value.split('.') {
+                ids.push(id.parse::<i64>().ok()?);
+            }
+
+            return Some(ids);
+        }
+
+        None
+    }
+
+    // Entries of the other vendors which have to be propagated to the next hop
+    pub fn get_foreign_trace_state(&self) -> Option<String> {
+        let trace_state = self.trace_state.as_ref()?;
+
+        let result: Vec<&str> = get_trace_state_members(trace_state)
+            .filter(|member| !is_my_telemetry_member(member))
+            .collect();
+
+        if result.is_empty() {
+            return None;
+        }
+
+        Some(result.join(","))
+    }
+
+    // Our entry goes first, as the spec requires for the modified entry
+    pub fn add_foreign_trace_state(&mut self, foreign_trace_state: Option<&str>) {
+        let Some(foreign_trace_state) = foreign_trace_state else {
+            return;
+        };
+
+        let mut members: Vec<&str> = match &self.trace_state {
+            Some(trace_state) => get_trace_state_members(trace_state).collect(),
+            None => Vec::new(),
+        };
+
+        members.extend(
+            get_trace_state_members(foreign_trace_state)
+                .filter(|member| !is_my_telemetry_member(member)),
+        );
+        members.truncate(MAX_TRACESTATE_MEMBERS);
+
+        if members.is_empty() {
+            return;
+        }
+
+        self.trace_state = Some(members.join(","));
+    }
+
+    // Trace ids produced by us carry the process id in the lower 64 bits. Foreign trace ids
+    // are folded to their lower 64 bits as well, so the mapping is stable for the whole trace
+    pub fn to_my_telemetry_context(&self) -> MyTelemetryContext {
+        match self.get_process_ids() {
+            Some(mut ids) if ids.len() == 1 => MyTelemetryContext::Single(ids.remove(0)),
+            Some(ids) => MyTelemetryContext::Multiple(ids),
+            None => MyTelemetryContext::Single(self.trace_id as u64 as i64),
+        }
+    }
+}
+
+impl From<&MyTelemetryContext> for W3cTraceContext {
+    fn from(ctx: &MyTelemetryContext) -> Self {
+        Self::from_my_telemetry_context(ctx)
+    }
+}
+
+impl MyTelemetryContext {
+    pub fn parse_from_traceparent(
+        traceparent: &str,
+        tracestate: Option<&str>,
+    ) -> Result<Self, String> {
+        let w3c_ctx = W3cTraceContext::parse(traceparent, tracestate)?;
+        let result = w3c_ctx.to_my_telemetry_context();
+
+        // Not sampled flag is remembered by the default interface
+        if !w3c_ctx.sampled {
+            result.mark_not_sampled();
+        }
+
+        Ok(result)
+    }
+
+    pub fn to_w3c_trace_context(&self) -> W3cTraceContext {
+        W3cTraceContext::from_my_telemetry_context(self)
+    }
+
+    pub fn to_traceparent(&self) -> Result<String, String> {
+        self.to_w3c_trace_context().to_traceparent()
+    }
+
+    pub fn to_tracestate(&self) -> Option<String> {
+        self.to_w3c_trace_context().trace_state
+    }
+}
+
+fn get_trace_state_members(trace_state: &str) -> impl Iterator<Item = &str> {
+    trace_state
+        .split(',')
+        .map(|member| member.trim())
+        .filter(|member| !member.is_empty())
+}
+
+fn is_my_telemetry_member(member: &str) -> bool {
+    match member.split_once('=') {
+        Some((key, _)) => key.trim() == TRACESTATE_KEY,
+        None => false,
+    }
+}
+
+fn process_id_to_trace_id(process_id: i64) -> u128 {
+    process_id as u64 as u128
+}
+
+fn compile_trace_state_value(ids: &[i64]) -> String {
+    let mut result = String::new();
+    for (no, id) in ids.iter().enumerate() {
+        if no > 0 {
+            result.push('.');
+        }
+        result.push_str(&id.to_string());
+    }
+    result
+}
+
+fn check_ids(trace_id: u128, parent_span_id: u64) -> Result<(), String> {
+    if trace_id == 0 {
+        return Err("traceparent trace-id can not be all zeros".to_string());
+    }
+
+    if parent_span_id == 0 {
+        return Err("traceparent parent-id can not be all zeros".to_string());
+    }
+
+    Ok(())
+}
+
+fn parse_hex_field(value: Option<&str>, len: usize, name: &str) -> Result<u128, String> {
+    let value = match value {
+        Some(value) => value,
+        None => return Err(format!("traceparent has no {} field", name)),
+    };
+
+    // Only lowercase hex is allowed by the spec
+    let is_lowercase_hex = value
+        .bytes()
+        .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+
+    if value.len() != len || !is_lowercase_hex {
+        return Err(format!("Invalid traceparent {} field: {}", name, value));
+    }
+
+    u128::from_str_radix(value, 16).map_err(|err| format!("{}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse() {
+        let result = W3cTraceContext::parse(TRACEPARENT, Some("mytelemetry=1.2")).unwrap();
+
+        assert_eq!(result.trace_id, 0x4bf92f3577b34da6a3ce929d0e0e4736);
+        assert_eq!(result.parent_span_id, 0x00f067aa0ba902b7);
+        assert!(result.sampled);
+        assert_eq!(result.get_process_ids(), Some(vec![1, 2]));
+        assert_eq!(result.to_traceparent().unwrap(), TRACEPARENT);
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_traceparent() {
+        let invalid = [
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00F067AA0BA902B7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-00",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        ];
+
+        for traceparent in invalid {
+            assert!(
+                W3cTraceContext::parse(traceparent, None).is_err(),
+                "{}",
+                traceparent
+            );
+        }
+    }
+
+    #[test]
+    fn test_zero_process_id_is_rejected() {
+        let ctx = MyTelemetryContext::Single(0);
+        assert!(ctx.to_traceparent().is_err());
+    }
+
+    #[test]
+    fn test_single_process_id_round_trip() {
+        for process_id in [1, 1_700_000_000_000_000, i64::MAX, -1, i64::MIN] {
+            let ctx = MyTelemetryContext::Single(process_id);
+
+            let traceparent = ctx.to_traceparent().unwrap();
+            let result = MyTelemetryContext::parse_from_traceparent(&traceparent, None).unwrap();
+
+            assert_eq!(result.as_string(), ctx.as_string());
+        }
+    }
+
+    #[test]
+    fn test_multiple_process_ids_round_trip() {
+        let ctx = MyTelemetryContext::Multiple(vec![1_700_000_000_000_000, 1_700_000_000_000_001]);
+
+        let traceparent = ctx.to_traceparent().unwrap();
+        let tracestate = ctx.to_tracestate();
+
+        let result =
+            MyTelemetryContext::parse_from_traceparent(&traceparent, tracestate.as_deref())
+                .unwrap();
+
+        assert_eq!(result.as_string(), ctx.as_string());
+    }
+
+    #[test]
+    fn test_foreign_trace_state_is_kept_after_our_entry() {
+        let incoming = W3cTraceContext::parse(
+            TRACEPARENT,
+            Some("mytelemetry=1.2, congo=t61rcWkgMzE,rojo=00f"),
+        )
+        .unwrap();
+
+        let foreign_trace_state = incoming.get_foreign_trace_state();
+        assert_eq!(
+            foreign_trace_state.as_deref(),
+            Some("congo=t61rcWkgMzE,rojo=00f")
+        );
+
+        let ctx = MyTelemetryContext::Multiple(vec![3, 4]);
+        let mut outgoing = ctx.to_w3c_trace_context();
+        outgoing.add_foreign_trace_state(foreign_trace_state.as_deref());
+
+        assert_eq!(
+            outgoing.trace_state.as_deref(),
+            Some("mytelemetry=3.4,congo=t61rcWkgMzE,rojo=00f")
+        );
+    }
+
+    #[test]
+    fn test_foreign_trace_state_is_limited() {
+        let foreign_trace_state: Vec<String> =
+            (0..40).map(|no| format!("vendor{}=value", no)).collect();
+
+        let mut outgoing = MyTelemetryContext::Multiple(vec![3, 4]).to_w3c_trace_context();
+        outgoing.add_foreign_trace_state(Some(foreign_trace_state.join(",").as_str()));
+
+        let trace_state = outgoing.trace_state.unwrap();
+        assert_eq!(trace_state.split(',').count(), MAX_TRACESTATE_MEMBERS);
+        assert!(trace_state.starts_with("mytelemetry=3.4,vendor0=value"));
+    }
+
+    #[test]
+    fn test_foreign_trace_id_uses_lower_64_bits() {
+        let result = MyTelemetryContext::parse_from_traceparent(TRACEPARENT, None).unwrap();
+
+        assert_eq!(
+            result.as_string(),
+            (0xa3ce929d0e0e4736_u64 as i64).to_string()
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use flurl::FlUrl;
+use my_telemetry_core::{EventDurationTracker, MyTelemetryContext};
+use rust_extensions::StrOrString;
+
+use crate::TrackedFlUrl;
+
+pub trait MyTelemetryFlUrlExt {
+    fn with_my_telemetry_context(self, my_telemetry: &MyTelemetryContext) -> Self;
+
+    // Span of the tracker becomes the parent span of the server event
+    fn with_my_telemetry_tracker(self, tracker: &EventDurationTracker) -> Self;
+
+    // Client event is the child of the ambient context. Without it the new process is started
+    fn track_my_telemetry(self, event_name: impl Into<StrOrString<'static>>) -> TrackedFlUrl;
+
+    fn track_my_telemetry_with_context(
+        self,
+        my_telemetry: &MyTelemetryContext,
+        event_name: impl Into<StrOrString<'static>>,
+    ) -> TrackedFlUrl;
+}
+
+impl MyTelemetryFlUrlExt for FlUrl {
+    fn with_my_telemetry_context(self, my_telemetry: &MyTelemetryContext) -> Self {
+        add_headers(self, my_telemetry.get_propagation_headers())
+    }
+
+    fn with_my_telemetry_tracker(self, tracker: &EventDurationTracker) -> Self {
+        add_headers(self, tracker.get_propagation_headers())
+    }
+
+    fn track_my_telemetry(self, event_name: impl Into<StrOrString<'static>>) -> TrackedFlUrl {
+        TrackedFlUrl::new(self, None, event_name)
+    }
+
+    fn track_my_telemetry_with_context(
+        self,
+        my_telemetry: &MyTelemetryContext,
+        event_name: impl Into<StrOrString<'static>>,
+    ) -> TrackedFlUrl {
+        TrackedFlUrl::new(self, Some(my_telemetry.clone()), event_name)
+    }
+}
+
+pub(crate) fn add_headers(mut fl_url: FlUrl, headers: Vec<(&'static str, String)>) -> FlUrl {
+    for (name, value) in headers {
+        fl_url = fl_url.with_header(name, value);
+    }
+
+    fl_url
+}
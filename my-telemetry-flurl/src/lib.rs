@@ -0,0 +1,4 @@
+mod flurl_ext;
+mod tracked_flurl;
+pub use flurl_ext::MyTelemetryFlUrlExt;
+pub use tracked_flurl::TrackedFlUrl;
@@ -0,0 +1,305 @@
+use std::{future::Future, sync::Arc};
+
+use flurl::{body::FlUrlBody, FlUrl, FlUrlError, FlUrlResponse};
+use my_telemetry_core::{EventDurationTracker, MyTelemetryContext, TelemetryInterface};
+use rust_extensions::StrOrString;
+
+// Request which writes the client event with the status code of the call.
+// Span of the client event is sent to the server as the parent span
+pub struct TrackedFlUrl {
+    fl_url: FlUrl,
+    event_name: StrOrString<'static>,
+    my_telemetry: Option<MyTelemetryContext>,
+    telemetry_interface: Option<Arc<TelemetryInterface>>,
+    tags: Vec<(StrOrString<'static>, StrOrString<'static>)>,
+}
+
+impl TrackedFlUrl {
+    // Without the context the event is the child of the ambient context or a new process
+    pub fn new(
+        fl_url: FlUrl,
+        my_telemetry: Option<MyTelemetryContext>,
+        event_name: impl Into<StrOrString<'static>>,
+    ) -> Self {
+        Self {
+            fl_url,
+            event_name: event_name.into(),
+            my_telemetry,
+            telemetry_interface: None,
+            tags: Vec::new(),
+        }
+    }
+
+    pub fn with_telemetry_interface(
+        mut self,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        self.telemetry_interface = Some(telemetry_interface);
+        self
+    }
+
+    pub fn add_tag(
+        mut self,
+        key: impl Into<StrOrString<'static>>,
+        value: impl Into<StrOrString<'static>>,
+    ) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    pub async fn get(self) -> Result<FlUrlResponse, FlUrlError> {
+        self.execute(|fl_url| fl_url.get()).await
+    }
+
+    pub async fn post(self, body: FlUrlBody) -> Result<FlUrlResponse, FlUrlError> {
+        self.execute(|fl_url| fl_url.post(body)).await
+    }
+
+    // For the methods which are not covered by get and post
+    pub async fn execute<TFuture>(
+        self,
+        request: impl FnOnce(FlUrl) -> TFuture,
+    ) -> Result<FlUrlResponse, FlUrlError>
+    where
+        TFuture: Future<Output = Result<FlUrlResponse, FlUrlError>>,
+    {
+        // Started here, so a new process is generated and sampled by the given interface
+        let mut tracker = match (&self.my_telemetry, self.telemetry_interface) {
+            (Some(my_telemetry), Some(telemetry_interface)) => {
+                telemetry_interface.start_event_tracking(my_telemetry, self.event_name)
+            }
+            (Some(my_telemetry), None) => my_telemetry.start_event_tracking(self.event_name),
+            (None, telemetry_interface) => {
+                EventDurationTracker::start_process(self.event_name, None, telemetry_interface)
+            }
+        };
+
+        for (key, value) in self.tags {
+            tracker.add_tag_as_ref(key, value);
+        }
+
+        let fl_url = crate::flurl_ext::add_headers(self.fl_url, tracker.get_propagation_headers());
+
+        let result = request(fl_url).await;
+
+        match &result {
+            Ok(response) => {
+                let status_code = response.get_status_code();
+                tracker.add_tag_as_ref("status_code", status_code.to_string());
+
+                // Unlike the server event 4xx is the failure of the client call
+                if status_code >= 400 {
+                    tracker.set_fail_result(format!("Status code {}", status_code));
+                } else {
+                    tracker.set_ok_result(format!("Status code {}", status_code));
+                }
+            }
+            Err(err) => {
+                tracker.set_fail_result(format!("{:?}", err));
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flurl::FlUrl;
+    use my_telemetry_core::{
+        HeadSampler, MyTelemetryContext, TelemetryEventMatcher, TelemetryTestHarness,
+        W3cTraceContext,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    use crate::MyTelemetryFlUrlExt;
+
+    const PROCESS_ID: i64 = 1_700_000_000_000_000;
+
+    // Answers one request with the status code and returns the headers of the request
+    async fn start_server(status_code: u16) -> (String, JoinHandle<Vec<(String, String)>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let handle = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 1024];
+            while !request.windows(4).any(|itm| itm == b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buffer[..read]);
+            }
+
+            let response = format!(
+                "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status_code
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+
+            String::from_utf8_lossy(&request)
+                .lines()
+                .skip(1)
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+                .collect()
+        });
+
+        (url, handle)
+    }
+
+    fn get_header<'s>(headers: &'s [(String, String)], name: &str) -> Option<&'s str> {
+        headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    #[tokio::test]
+    async fn test_context_headers_are_sent() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let (url, server) = start_server(200).await;
+
+        FlUrl::new(&url)
+            .track_my_telemetry_with_context(&MyTelemetryContext::Single(PROCESS_ID), "GET orders")
+            .with_telemetry_interface(harness.get_telemetry_interface().clone())
+            .get()
+            .await
+            .unwrap();
+
+        let headers = server.await.unwrap();
+        assert_eq!(
+            get_header(&headers, "process-id"),
+            Some(PROCESS_ID.to_string().as_str())
+        );
+
+        let events = harness
+            .find(&TelemetryEventMatcher::event("GET orders").with_process_id(PROCESS_ID))
+            .await;
+        assert_eq!(events.len(), 1);
+
+        // Span of the client event is the parent span of the server
+        let trace_context =
+            W3cTraceContext::parse(get_header(&headers, "traceparent").unwrap(), None).unwrap();
+        assert_eq!(Some(trace_context.parent_span_id as i64), events[0].span_id);
+    }
+
+    #[tokio::test]
+    async fn test_success_status_is_ok() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let (url, server) = start_server(204).await;
+
+        FlUrl::new(&url)
+            .track_my_telemetry_with_context(&MyTelemetryContext::Single(PROCESS_ID), "GET orders")
+            .with_telemetry_interface(harness.get_telemetry_interface().clone())
+            .get()
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("GET orders")
+                    .with_tag("status_code", "204")
+                    .with_success(),
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_client_error_status_is_fail() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let (url, server) = start_server(404).await;
+
+        FlUrl::new(&url)
+            .track_my_telemetry_with_context(&MyTelemetryContext::Single(PROCESS_ID), "GET orders")
+            .with_telemetry_interface(harness.get_telemetry_interface().clone())
+            .add_tag("order_id", "42")
+            .get()
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("GET orders")
+                    .with_tag("order_id", "42")
+                    .with_tag("status_code", "404")
+                    .with_fail_message("Status code 404"),
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_request_error_is_fail() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+
+        // Nobody listens on the port after the listener is dropped
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let result = FlUrl::new(&url)
+            .track_my_telemetry_with_context(&MyTelemetryContext::Single(PROCESS_ID), "GET orders")
+            .with_telemetry_interface(harness.get_telemetry_interface().clone())
+            .get()
+            .await;
+        assert!(result.is_err());
+
+        harness
+            .assert_recorded(&TelemetryEventMatcher::event("GET orders").with_fail())
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_new_process_is_started_by_the_interface() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let (url, server) = start_server(200).await;
+
+        FlUrl::new(&url)
+            .track_my_telemetry("GET orders")
+            .with_telemetry_interface(harness.get_telemetry_interface().clone())
+            .get()
+            .await
+            .unwrap();
+
+        let headers = server.await.unwrap();
+        let events = harness
+            .find(&TelemetryEventMatcher::event("GET orders").with_success())
+            .await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            get_header(&headers, "process-id"),
+            Some(events[0].process_id.to_string().as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_process_is_sampled_by_the_interface() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        harness
+            .get_telemetry_interface()
+            .set_head_sampler(HeadSampler::new().with_ratio(0.0));
+        let (url, server) = start_server(200).await;
+
+        FlUrl::new(&url)
+            .track_my_telemetry("GET orders")
+            .with_telemetry_interface(harness.get_telemetry_interface().clone())
+            .get()
+            .await
+            .unwrap();
+        server.await.unwrap();
+
+        harness
+            .assert_not_recorded(&TelemetryEventMatcher::event("GET orders"))
+            .await;
+    }
+}
@@ -0,0 +1,123 @@
+use my_telemetry_core::{
+    AmbientTelemetryContext, MyTelemetryContext, PROCESS_ID_HEADER, TRACEPARENT_HEADER,
+};
+use tonic::{service::Interceptor, Request, Status};
+
+use crate::request_ext::insert_headers;
+
+// Puts the context to the metadata of every request. Without the fixed context
+// the ambient context of the calling task is used, its span becomes the parent span
+// of the server event
+#[derive(Debug, Clone, Default)]
+pub struct MyTelemetryInterceptor {
+    my_telemetry: Option<MyTelemetryContext>,
+}
+
+impl MyTelemetryInterceptor {
+    pub fn new() -> Self {
+        Self { my_telemetry: None }
+    }
+
+    pub fn with_context(my_telemetry: MyTelemetryContext) -> Self {
+        Self {
+            my_telemetry: Some(my_telemetry),
+        }
+    }
+}
+
+impl Interceptor for MyTelemetryInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        // Context which is set to the request explicitly wins
+        let metadata = request.metadata();
+        if metadata.contains_key(PROCESS_ID_HEADER) || metadata.contains_key(TRACEPARENT_HEADER) {
+            return Ok(request);
+        }
+
+        let headers = match &self.my_telemetry {
+            Some(my_telemetry) => my_telemetry.get_propagation_headers(),
+            None => match AmbientTelemetryContext::current() {
+                Some(ambient) => ambient.get_propagation_headers(),
+                None => return Ok(request),
+            },
+        };
+
+        insert_headers(&mut request, headers);
+
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use my_telemetry_core::{TelemetryInterface, W3cTraceContext};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::MyTelemetryRequestExt;
+
+    fn get_traceparent(request: &Request<()>) -> W3cTraceContext {
+        let traceparent = request.metadata().get(TRACEPARENT_HEADER).unwrap();
+        W3cTraceContext::parse(traceparent.to_str().unwrap(), None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_ambient_tracker_is_the_parent_span() {
+        let telemetry_interface = Arc::new(TelemetryInterface::new());
+        let tracker = telemetry_interface.start_event_tracking(
+            &MyTelemetryContext::Single(1_700_000_000_000_000),
+            "request",
+        );
+
+        let request = tracker
+            .scope(async { MyTelemetryInterceptor::new().call(Request::new(())) })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            request.get_my_telemetry_context().unwrap().as_string(),
+            "1700000000000000"
+        );
+        assert_eq!(
+            get_traceparent(&request).parent_span_id,
+            tracker.span_id as u64
+        );
+    }
+
+    #[test]
+    fn test_fixed_context_is_used() {
+        let ctx = MyTelemetryContext::Multiple(vec![1_700_000_000_000_000, 1_700_000_000_000_001]);
+
+        let request = MyTelemetryInterceptor::with_context(ctx.clone())
+            .call(Request::new(()))
+            .unwrap();
+
+        assert_eq!(
+            request.get_my_telemetry_context().unwrap().as_string(),
+            ctx.as_string()
+        );
+    }
+
+    #[test]
+    fn test_context_of_the_request_wins() {
+        let mut request = Request::new(());
+        request.set_my_telemetry_context(&MyTelemetryContext::Single(1_700_000_000_000_000));
+
+        let request = MyTelemetryInterceptor::with_context(MyTelemetryContext::Single(42))
+            .call(request)
+            .unwrap();
+
+        assert_eq!(
+            request.get_my_telemetry_context().unwrap().as_string(),
+            "1700000000000000"
+        );
+    }
+
+    #[test]
+    fn test_request_without_context_is_not_changed() {
+        let request = MyTelemetryInterceptor::new()
+            .call(Request::new(()))
+            .unwrap();
+
+        assert!(request.metadata().is_empty());
+    }
+}
@@ -0,0 +1,8 @@
+mod client_interceptor;
+mod request_ext;
+mod server_layer;
+mod tracked_body;
+pub use client_interceptor::MyTelemetryInterceptor;
+pub use request_ext::MyTelemetryRequestExt;
+pub use server_layer::{MyTelemetryGrpcLayer, MyTelemetryGrpcService};
+pub use tracked_body::TrackedBody;
@@ -0,0 +1,106 @@
+use my_telemetry_core::{EventDurationTracker, IncomingTelemetryContext, MyTelemetryContext};
+use tonic::{metadata::MetadataValue, Request};
+
+pub trait MyTelemetryRequestExt {
+    fn set_my_telemetry_context(&mut self, my_telemetry: &MyTelemetryContext);
+
+    // Span of the tracker becomes the parent span of the server event
+    fn set_my_telemetry_tracker(&mut self, tracker: &EventDurationTracker);
+
+    fn get_my_telemetry_context(&self) -> Option<MyTelemetryContext>;
+}
+
+impl<T> MyTelemetryRequestExt for Request<T> {
+    fn set_my_telemetry_context(&mut self, my_telemetry: &MyTelemetryContext) {
+        insert_headers(self, my_telemetry.get_propagation_headers());
+    }
+
+    fn set_my_telemetry_tracker(&mut self, tracker: &EventDurationTracker) {
+        insert_headers(self, tracker.get_propagation_headers());
+    }
+
+    // Server layer puts the context to the extensions. Otherwise it is parsed from the metadata
+    fn get_my_telemetry_context(&self) -> Option<MyTelemetryContext> {
+        if let Some(my_telemetry) = self.extensions().get::<MyTelemetryContext>() {
+            return Some(my_telemetry.clone());
+        }
+
+        let metadata = self.metadata();
+        let incoming = IncomingTelemetryContext::parse(|name| {
+            metadata.get(name).and_then(|itm| itm.to_str().ok())
+        })?;
+
+        Some(incoming.my_telemetry)
+    }
+}
+
+pub(crate) fn insert_headers<T>(request: &mut Request<T>, headers: Vec<(&'static str, String)>) {
+    let metadata = request.metadata_mut();
+
+    for (name, value) in headers {
+        if let Ok(value) = MetadataValue::try_from(value) {
+            metadata.insert(name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use my_telemetry_core::{PROCESS_ID_HEADER, TRACEPARENT_HEADER};
+
+    use super::*;
+
+    #[test]
+    fn test_context_of_extensions_wins() {
+        let mut request = Request::new(());
+        request.set_my_telemetry_context(&MyTelemetryContext::Single(1_700_000_000_000_000));
+        request
+            .extensions_mut()
+            .insert(MyTelemetryContext::Single(1_700_000_000_000_001));
+
+        assert_eq!(
+            request.get_my_telemetry_context().unwrap().as_string(),
+            "1700000000000001"
+        );
+    }
+
+    #[test]
+    fn test_context_is_parsed_from_traceparent() {
+        let mut request = Request::new(());
+        request.metadata_mut().insert(
+            TRACEPARENT_HEADER,
+            MetadataValue::from_static("00-000000000000000000060a24181e4000-00f067aa0ba902b7-01"),
+        );
+
+        assert_eq!(
+            request.get_my_telemetry_context().unwrap().as_string(),
+            "1700000000000000"
+        );
+    }
+
+    #[test]
+    fn test_tracker_headers_are_set() {
+        let tracker =
+            MyTelemetryContext::Single(1_700_000_000_000_000).start_event_tracking("request");
+
+        let mut request = Request::new(());
+        request.set_my_telemetry_tracker(&tracker);
+
+        let metadata = request.metadata();
+        assert_eq!(
+            metadata.get(PROCESS_ID_HEADER).unwrap().to_str().unwrap(),
+            "1700000000000000"
+        );
+        assert!(metadata
+            .get(TRACEPARENT_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .contains(&format!("-{:016x}-", tracker.span_id)));
+    }
+
+    #[test]
+    fn test_request_without_context() {
+        assert!(Request::new(()).get_my_telemetry_context().is_none());
+    }
+}
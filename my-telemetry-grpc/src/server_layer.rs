@@ -0,0 +1,211 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use http::{Request, Response};
+use my_telemetry_core::{IncomingTelemetryContext, MyTelemetryFutureExt, TelemetryInterface};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{tracked_body::set_grpc_status, TrackedBody};
+
+// Starts the event named after the RPC method for every call. Context of the caller is taken
+// from the process-id or traceparent metadata and is the ambient context of the handler
+#[derive(Clone)]
+pub struct MyTelemetryGrpcLayer {
+    telemetry_interface: Arc<TelemetryInterface>,
+}
+
+impl MyTelemetryGrpcLayer {
+    pub fn new() -> Self {
+        Self {
+            telemetry_interface: my_telemetry_core::TELEMETRY_INTERFACE.clone(),
+        }
+    }
+
+    pub fn with_telemetry_interface(
+        mut self,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        self.telemetry_interface = telemetry_interface;
+        self
+    }
+}
+
+impl Default for MyTelemetryGrpcLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for MyTelemetryGrpcLayer {
+    type Service = MyTelemetryGrpcService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MyTelemetryGrpcService {
+            inner,
+            telemetry_interface: self.telemetry_interface.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MyTelemetryGrpcService<S> {
+    inner: S,
+    telemetry_interface: Arc<TelemetryInterface>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for MyTelemetryGrpcService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<TrackedBody<ResBody>>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let headers = request.headers();
+        let incoming = IncomingTelemetryContext::parse(|name| {
+            headers.get(name).and_then(|itm| itm.to_str().ok())
+        });
+
+        let mut tracker = IncomingTelemetryContext::start_event_tracking(
+            incoming,
+            &self.telemetry_interface,
+            request.uri().path().to_string(),
+        );
+
+        request
+            .extensions_mut()
+            .insert(tracker.my_telemetry.clone());
+
+        let future = self.inner.call(request).with_tracker(&tracker);
+
+        Box::pin(async move {
+            let response = match future.await {
+                Ok(response) => response,
+                Err(err) => {
+                    tracker.set_fail_result("Service error".to_string());
+                    return Err(err);
+                }
+            };
+
+            // Errors are sent as trailers-only response, so the status is in the headers
+            if set_grpc_status(&mut tracker, response.headers()) {
+                let (parts, body) = response.into_parts();
+                drop(tracker);
+                return Ok(Response::from_parts(parts, TrackedBody::new(body, None)));
+            }
+
+            let (parts, body) = response.into_parts();
+            Ok(Response::from_parts(
+                parts,
+                TrackedBody::new(body, Some(tracker)),
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        future::{ready, Ready},
+        task::{Context, Poll},
+    };
+
+    use http::{Request, Response};
+    use my_telemetry_core::{TelemetryEventMatcher, TelemetryTestHarness};
+    use tower_layer::Layer;
+    use tower_service::Service;
+
+    use super::MyTelemetryGrpcLayer;
+
+    // Answers with trailers-only response, the way tonic sends errors
+    #[derive(Clone)]
+    struct StatusService {
+        grpc_status: &'static str,
+        grpc_message: &'static str,
+    }
+
+    impl Service<Request<()>> for StatusService {
+        type Response = Response<String>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: Request<()>) -> Self::Future {
+            ready(Ok(Response::builder()
+                .header("content-type", "application/grpc")
+                .header("grpc-status", self.grpc_status)
+                .header("grpc-message", self.grpc_message)
+                .body(String::new())
+                .unwrap()))
+        }
+    }
+
+    async fn call(
+        layer: &MyTelemetryGrpcLayer,
+        grpc_status: &'static str,
+        grpc_message: &'static str,
+    ) {
+        let mut service = layer.layer(StatusService {
+            grpc_status,
+            grpc_message,
+        });
+
+        let request = Request::builder()
+            .uri("/orders.Orders/GetOrder")
+            .header("process-id", "1700000000000000")
+            .body(())
+            .unwrap();
+
+        service.call(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_ok_status_is_written_as_success() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let layer = MyTelemetryGrpcLayer::new()
+            .with_telemetry_interface(harness.get_telemetry_interface().clone());
+
+        call(&layer, "0", "").await;
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("/orders.Orders/GetOrder")
+                    .with_process_id(1_700_000_000_000_000)
+                    .with_success(),
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_error_status_is_written_as_fail() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let layer = MyTelemetryGrpcLayer::new()
+            .with_telemetry_interface(harness.get_telemetry_interface().clone());
+
+        call(&layer, "5", "Order%2042%20is%20not%20found").await;
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("/orders.Orders/GetOrder")
+                    .with_process_id(1_700_000_000_000_000)
+                    .with_tag("grpc_status", "5")
+                    .with_fail_message("NotFound: Order 42 is not found"),
+            )
+            .await;
+    }
+}
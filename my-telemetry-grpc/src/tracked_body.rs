@@ -0,0 +1,222 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use http::HeaderMap;
+use http_body::{Body, Frame, SizeHint};
+use my_telemetry_core::EventDurationTracker;
+
+pin_project_lite::pin_project! {
+    // Keeps the tracker until the response is streamed, so the event covers the whole call
+    // and gets the status from the trailers
+    pub struct TrackedBody<B> {
+        #[pin]
+        inner: B,
+        tracker: Option<EventDurationTracker>,
+    }
+}
+
+impl<B> TrackedBody<B> {
+    pub(crate) fn new(inner: B, tracker: Option<EventDurationTracker>) -> Self {
+        Self { inner, tracker }
+    }
+}
+
+impl<B: Body> Body for TrackedBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+
+        let result = match this.inner.poll_frame(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+
+        match &result {
+            Some(Ok(frame)) => {
+                if let Some(trailers) = frame.trailers_ref() {
+                    if let Some(tracker) = this.tracker.as_mut() {
+                        set_grpc_status(tracker, trailers);
+                    }
+                }
+            }
+            Some(Err(_)) => {
+                if let Some(mut tracker) = this.tracker.take() {
+                    tracker.set_fail_result("Response stream error".to_string());
+                }
+            }
+            None => {
+                this.tracker.take();
+            }
+        }
+
+        Poll::Ready(result)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+// Returns false if there is no status in the headers
+pub(crate) fn set_grpc_status(tracker: &mut EventDurationTracker, headers: &HeaderMap) -> bool {
+    let status = match headers
+        .get("grpc-status")
+        .and_then(|itm| itm.to_str().ok())
+        .and_then(|itm| itm.parse::<i32>().ok())
+    {
+        Some(status) => status,
+        None => return false,
+    };
+
+    let code = tonic::Code::from(status);
+
+    if code == tonic::Code::Ok {
+        tracker.set_ok_result("OK");
+        return true;
+    }
+
+    // grpc-message is percent-encoded UTF-8
+    let message = match headers.get("grpc-message") {
+        Some(message) => percent_encoding::percent_decode(message.as_bytes())
+            .decode_utf8_lossy()
+            .to_string(),
+        None => String::new(),
+    };
+
+    tracker.add_tag_as_ref("grpc_status", format!("{}", status));
+    tracker.set_fail_result(format!("{:?}: {}", code, message));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, future::poll_fn};
+
+    use http::HeaderValue;
+    use my_telemetry_core::{MyTelemetryContext, TelemetryEventMatcher, TelemetryTestHarness};
+
+    use super::*;
+
+    struct TestBody {
+        frames: VecDeque<Result<Frame<&'static [u8]>, String>>,
+    }
+
+    impl TestBody {
+        fn new(frames: Vec<Result<Frame<&'static [u8]>, String>>) -> Self {
+            Self {
+                frames: frames.into(),
+            }
+        }
+    }
+
+    impl Body for TestBody {
+        type Data = &'static [u8];
+        type Error = String;
+
+        fn poll_frame(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+        ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+            Poll::Ready(self.frames.pop_front())
+        }
+    }
+
+    fn create_trailers(status: &'static str, message: Option<&'static str>) -> HeaderMap {
+        let mut result = HeaderMap::new();
+        result.insert("grpc-status", HeaderValue::from_static(status));
+        if let Some(message) = message {
+            result.insert("grpc-message", HeaderValue::from_static(message));
+        }
+        result
+    }
+
+    async fn read_to_end<B: Body + Unpin>(mut body: B) {
+        while let Some(frame) = poll_fn(|cx| Pin::new(&mut body).poll_frame(cx)).await {
+            if frame.is_err() {
+                return;
+            }
+        }
+    }
+
+    async fn read_tracked(
+        harness: &TelemetryTestHarness,
+        frames: Vec<Result<Frame<&'static [u8]>, String>>,
+    ) {
+        let tracker = harness
+            .get_telemetry_interface()
+            .start_event_tracking(&MyTelemetryContext::Single(1_700_000_000_000_000), "call");
+
+        read_to_end(TrackedBody::new(TestBody::new(frames), Some(tracker))).await;
+    }
+
+    #[tokio::test]
+    async fn test_status_is_taken_from_trailers() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+
+        read_tracked(
+            &harness,
+            vec![
+                Ok(Frame::data(b"data".as_slice())),
+                Ok(Frame::trailers(create_trailers("0", None))),
+            ],
+        )
+        .await;
+
+        harness
+            .assert_recorded(&TelemetryEventMatcher::event("call").with_success())
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_grpc_message_is_percent_decoded() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+
+        read_tracked(
+            &harness,
+            vec![Ok(Frame::trailers(create_trailers(
+                "5",
+                Some("Order%20is%20not%20found%3A%20%D0%B9"),
+            )))],
+        )
+        .await;
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("call")
+                    .with_tag("grpc_status", "5")
+                    .with_fail_message("NotFound: Order is not found: й"),
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_is_fail() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+
+        read_tracked(
+            &harness,
+            vec![
+                Ok(Frame::data(b"data".as_slice())),
+                Err("connection reset".to_string()),
+            ],
+        )
+        .await;
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("call").with_fail_message("Response stream error"),
+            )
+            .await;
+    }
+}
@@ -0,0 +1,3 @@
+mod request_info;
+mod server_layer;
+pub use server_layer::{MyTelemetryHttpLayer, MyTelemetryHttpService, RouteResolver};
@@ -0,0 +1,44 @@
+use http::Request;
+
+// Route template is known only when the router puts it to the extensions
+pub fn get_route<B>(request: &Request<B>) -> Option<String> {
+    #[cfg(feature = "axum")]
+    if let Some(matched_path) = request.extensions().get::<axum::extract::MatchedPath>() {
+        return Some(matched_path.as_str().to_string());
+    }
+
+    #[cfg(not(feature = "axum"))]
+    let _ = request;
+
+    None
+}
+
+pub fn get_client_ip<B>(request: &Request<B>) -> Option<String> {
+    let headers = request.headers();
+
+    if let Some(forwarded_for) = headers
+        .get("x-forwarded-for")
+        .and_then(|itm| itm.to_str().ok())
+    {
+        if let Some(ip) = forwarded_for.split(',').next() {
+            let ip = ip.trim();
+            if !ip.is_empty() {
+                return Some(ip.to_string());
+            }
+        }
+    }
+
+    if let Some(real_ip) = headers.get("x-real-ip").and_then(|itm| itm.to_str().ok()) {
+        return Some(real_ip.trim().to_string());
+    }
+
+    #[cfg(feature = "axum")]
+    if let Some(connect_info) = request
+        .extensions()
+        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+    {
+        return Some(connect_info.0.ip().to_string());
+    }
+
+    None
+}
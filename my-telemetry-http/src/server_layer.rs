@@ -0,0 +1,258 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use http::{Request, Response};
+use my_telemetry_core::{
+    IncomingTelemetryContext, MyTelemetryFutureExt, TelemetryEventTagsBuilder, TelemetryInterface,
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::request_info::{get_client_ip, get_route};
+
+// Resolves the route template of the path. Paths without the template give None
+pub type RouteResolver = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+// Starts the event "METHOD route" for every request. Context of the caller is taken from
+// the process-id or traceparent headers and is the ambient context of the handler
+#[derive(Clone)]
+pub struct MyTelemetryHttpLayer {
+    telemetry_interface: Arc<TelemetryInterface>,
+    route_resolver: Option<RouteResolver>,
+}
+
+impl MyTelemetryHttpLayer {
+    pub fn new() -> Self {
+        Self {
+            telemetry_interface: my_telemetry_core::TELEMETRY_INTERFACE.clone(),
+            route_resolver: None,
+        }
+    }
+
+    pub fn with_telemetry_interface(
+        mut self,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        self.telemetry_interface = telemetry_interface;
+        self
+    }
+
+    // Used when the router does not give the route template. Raw paths are not used as the
+    // event name, otherwise every id in the path would be the separate event
+    pub fn with_route_resolver(
+        mut self,
+        route_resolver: impl Fn(&str) -> Option<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.route_resolver = Some(Arc::new(route_resolver));
+        self
+    }
+}
+
+impl Default for MyTelemetryHttpLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for MyTelemetryHttpLayer {
+    type Service = MyTelemetryHttpService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MyTelemetryHttpService {
+            inner,
+            telemetry_interface: self.telemetry_interface.clone(),
+            route_resolver: self.route_resolver.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MyTelemetryHttpService<S> {
+    inner: S,
+    telemetry_interface: Arc<TelemetryInterface>,
+    route_resolver: Option<RouteResolver>,
+}
+
+impl<S> MyTelemetryHttpService<S> {
+    // Event is named after the method only if the route template is unknown
+    fn get_event_name<B>(&self, request: &Request<B>) -> String {
+        let route = get_route(request).or_else(|| {
+            let route_resolver = self.route_resolver.as_ref()?;
+            route_resolver(request.uri().path())
+        });
+
+        match route {
+            Some(route) => format!("{} {}", request.method(), route),
+            None => request.method().to_string(),
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for MyTelemetryHttpService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let headers = request.headers();
+        let incoming = IncomingTelemetryContext::parse(|name| {
+            headers.get(name).and_then(|itm| itm.to_str().ok())
+        });
+
+        let event_name = self.get_event_name(&request);
+
+        let mut tracker = IncomingTelemetryContext::start_event_tracking(
+            incoming,
+            &self.telemetry_interface,
+            event_name,
+        );
+
+        let mut tags =
+            TelemetryEventTagsBuilder::new().add("path", request.uri().path().to_string());
+
+        if let Some(ip) = get_client_ip(&request) {
+            tags = tags.add_ip(ip);
+        }
+
+        request
+            .extensions_mut()
+            .insert(tracker.my_telemetry.clone());
+
+        let future = self.inner.call(request).with_tracker(&tracker);
+
+        Box::pin(async move {
+            let result = future.await;
+
+            match &result {
+                Ok(response) => {
+                    let status = response.status();
+                    tags = tags.add("status_code", status.as_u16().to_string());
+
+                    if status.is_server_error() {
+                        tracker.set_fail_result(format!("Status code {}", status));
+                    } else {
+                        tracker.set_ok_result(format!("Status code {}", status));
+                    }
+                }
+                Err(_) => {
+                    tracker.set_fail_result("Service error".to_string());
+                }
+            }
+
+            for tag in tags.tags {
+                tracker.add_tag_as_ref(tag.key, tag.value);
+            }
+
+            result
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        future::{ready, Ready},
+        task::{Context, Poll},
+    };
+
+    use http::{Method, Request, Response, StatusCode};
+    use my_telemetry_core::{TelemetryEventMatcher, TelemetryTestHarness};
+    use tower_layer::Layer;
+    use tower_service::Service;
+
+    use super::MyTelemetryHttpLayer;
+
+    #[derive(Clone)]
+    struct StatusService {
+        status: StatusCode,
+    }
+
+    impl Service<Request<()>> for StatusService {
+        type Response = Response<()>;
+        type Error = Infallible;
+        type Future = Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: Request<()>) -> Self::Future {
+            ready(Ok(Response::builder()
+                .status(self.status)
+                .body(())
+                .unwrap()))
+        }
+    }
+
+    async fn call(layer: &MyTelemetryHttpLayer, method: Method, path: &str, status: StatusCode) {
+        let mut service = layer.layer(StatusService { status });
+
+        let request = Request::builder()
+            .method(method)
+            .uri(path)
+            .body(())
+            .unwrap();
+
+        service.call(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_is_written_as_event_of_route() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let layer = MyTelemetryHttpLayer::new()
+            .with_telemetry_interface(harness.get_telemetry_interface().clone())
+            .with_route_resolver(|path| {
+                path.starts_with("/orders/")
+                    .then(|| "/orders/{id}".to_string())
+            });
+
+        call(&layer, Method::GET, "/orders/42", StatusCode::OK).await;
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("GET /orders/{id}")
+                    .with_tag("path", "/orders/42")
+                    .with_tag("status_code", "200")
+                    .with_success(),
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_server_error_is_written_as_fail() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let layer = MyTelemetryHttpLayer::new()
+            .with_telemetry_interface(harness.get_telemetry_interface().clone());
+
+        call(
+            &layer,
+            Method::POST,
+            "/orders/42",
+            StatusCode::INTERNAL_SERVER_ERROR,
+        )
+        .await;
+
+        // Path without the route template does not become the event name
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("POST")
+                    .with_tag("path", "/orders/42")
+                    .with_tag("status_code", "500")
+                    .with_fail(),
+            )
+            .await;
+    }
+}
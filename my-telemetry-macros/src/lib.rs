@@ -0,0 +1,12 @@
+mod track;
+mod track_attributes;
+
+use proc_macro::TokenStream;
+
+#[proc_macro_attribute]
+pub fn track(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match track::generate(attr.into(), item.into()) {
+        Ok(result) => result.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
@@ -0,0 +1,138 @@
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use quote::quote;
+use syn::{FnArg, ItemFn, Pat, ReturnType, Signature, Type};
+
+use crate::track_attributes::TrackAttributes;
+
+pub fn generate(attr: TokenStream, item: TokenStream) -> syn::Result<TokenStream> {
+    let attributes = TrackAttributes::parse(attr)?;
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = syn::parse2(item)?;
+
+    let event_name = match &attributes.name {
+        Some(name) => name.value(),
+        None => sig.ident.to_string(),
+    };
+
+    for tag in &attributes.tags {
+        if find_argument(&sig, |name, _| name == tag).is_none() {
+            return Err(syn::Error::new(
+                tag.span(),
+                format!("Argument {} is not found", tag),
+            ));
+        }
+    }
+
+    // Without &MyTelemetryContext argument the ambient context is used
+    let start_tracking = match find_argument(&sig, |_, tp| is_context_ref(tp)) {
+        Some(ctx) => quote!(#ctx.start_event_tracking(#event_name)),
+        None => quote!(::my_telemetry::EventDurationTracker::new(#event_name, None)),
+    };
+
+    let tags = attributes.tags.iter().map(|tag| {
+        let key = tag.to_string();
+        quote!(__my_telemetry_tracker.add_tag_as_ref(#key, #tag.to_string());)
+    });
+
+    // impl Trait can not be the type of the variable, even nested as Result<impl Trait, E>
+    let annotation = match &sig.output {
+        ReturnType::Default => quote!(: ()),
+        ReturnType::Type(_, tp) if contains_impl(quote!(#tp)) => quote!(),
+        ReturnType::Type(_, tp) => quote!(: #tp),
+    };
+
+    // Body is the closure or the async block so return and ? do not skip the tracking.
+    // It is executed in the span of the tracker, so events started inside are its children
+    let body = if sig.asyncness.is_some() {
+        quote! {
+            ::my_telemetry::MyTelemetryFutureExt::with_tracker(
+                async move {
+                    let __my_telemetry_result #annotation = #block;
+                    __my_telemetry_result
+                },
+                &__my_telemetry_tracker,
+            )
+            .await
+        }
+    } else {
+        quote! {
+            __my_telemetry_tracker.sync_scope(move || {
+                let __my_telemetry_result #annotation = #block;
+                __my_telemetry_result
+            })
+        }
+    };
+
+    let result = if returns_result(&sig.output) {
+        quote!(__my_telemetry_tracker.track_result(__my_telemetry_result))
+    } else {
+        quote!(__my_telemetry_result)
+    };
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis #sig {
+            #[allow(unused_mut)]
+            let mut __my_telemetry_tracker = #start_tracking;
+            #(#tags)*
+            #[allow(unreachable_code)]
+            let __my_telemetry_result #annotation = #body;
+            #result
+        }
+    })
+}
+
+fn find_argument(sig: &Signature, predicate: impl Fn(&Ident, &Type) -> bool) -> Option<&Ident> {
+    sig.inputs.iter().find_map(|arg| {
+        let FnArg::Typed(arg) = arg else {
+            return None;
+        };
+
+        let Pat::Ident(pat) = arg.pat.as_ref() else {
+            return None;
+        };
+
+        if predicate(&pat.ident, &arg.ty) {
+            Some(&pat.ident)
+        } else {
+            None
+        }
+    })
+}
+
+fn contains_impl(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "impl",
+        TokenTree::Group(group) => contains_impl(group.stream()),
+        _ => false,
+    })
+}
+
+fn is_context_ref(tp: &Type) -> bool {
+    match tp {
+        Type::Reference(reference) => is_path_to(&reference.elem, "MyTelemetryContext"),
+        _ => false,
+    }
+}
+
+// Result<T, E>, io::Result<T> and the other aliases named Result are supported
+fn returns_result(output: &ReturnType) -> bool {
+    match output {
+        ReturnType::Type(_, tp) => is_path_to(tp, "Result"),
+        ReturnType::Default => false,
+    }
+}
+
+fn is_path_to(tp: &Type, name: &str) -> bool {
+    match tp {
+        Type::Path(path) => match path.path.segments.last() {
+            Some(segment) => segment.ident == name,
+            None => false,
+        },
+        _ => false,
+    }
+}
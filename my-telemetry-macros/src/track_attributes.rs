@@ -0,0 +1,38 @@
+use proc_macro2::TokenStream;
+use syn::{meta::ParseNestedMeta, Ident, LitStr};
+
+#[derive(Default)]
+pub struct TrackAttributes {
+    pub name: Option<LitStr>,
+    pub tags: Vec<Ident>,
+}
+
+impl TrackAttributes {
+    pub fn parse(attr: TokenStream) -> syn::Result<Self> {
+        let mut result = Self::default();
+
+        let parser = syn::meta::parser(|meta| result.parse_meta(meta));
+        syn::parse::Parser::parse2(parser, attr)?;
+
+        Ok(result)
+    }
+
+    fn parse_meta(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse()?);
+            return Ok(());
+        }
+
+        if meta.path.is_ident("tags") {
+            return meta.parse_nested_meta(|tag| match tag.path.get_ident() {
+                Some(ident) => {
+                    self.tags.push(ident.clone());
+                    Ok(())
+                }
+                None => Err(tag.error("Tag must be the name of the argument")),
+            });
+        }
+
+        Err(meta.error("Supported attributes are: name, tags"))
+    }
+}
@@ -0,0 +1,8 @@
+#[test]
+fn test_compile() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/nested_impl_trait.rs");
+    cases.compile_fail("tests/ui/unknown_tag.rs");
+    cases.compile_fail("tests/ui/tag_is_not_argument_name.rs");
+    cases.compile_fail("tests/ui/unknown_attribute.rs");
+}
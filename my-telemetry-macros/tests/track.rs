@@ -0,0 +1,149 @@
+use my_telemetry::{track, MyTelemetryContext, TelemetryEventMatcher, TelemetryTestHarness};
+
+#[track(tags(value))]
+fn parse_number(value: &str) -> Result<i32, std::num::ParseIntError> {
+    let number: i32 = value.parse()?;
+    Ok(number)
+}
+
+#[track(name = "load-order")]
+async fn load_order(ctx: &MyTelemetryContext, order_id: i32) -> Result<i32, String> {
+    if order_id < 0 {
+        return Err(format!("Order {} is not found", order_id));
+    }
+
+    Ok(order_id)
+}
+
+#[track]
+fn get_even_numbers(count: usize) -> impl Iterator<Item = usize> {
+    (0..count).filter(|itm| itm % 2 == 0)
+}
+
+#[track]
+fn get_odd_numbers(count: usize) -> Result<impl Iterator<Item = usize>, String> {
+    if count == 0 {
+        return Err("Count is zero".to_string());
+    }
+
+    Ok((0..count).filter(|itm| itm % 2 == 1))
+}
+
+#[track]
+fn get_first<T: Clone>(items: &[T]) -> Option<T> {
+    items.first().cloned()
+}
+
+struct OrdersRepository {
+    prefix: String,
+}
+
+impl OrdersRepository {
+    #[track]
+    fn get_order_name(&self, order_id: i32) -> String {
+        format!("{}-{}", self.prefix, order_id)
+    }
+
+    #[track]
+    async fn get_order_name_async(&self, order_id: i32) -> String {
+        format!("{}-{}", self.prefix, order_id)
+    }
+}
+
+#[tokio::test]
+async fn test_sync_function_with_question_mark() {
+    let harness = TelemetryTestHarness::install().await;
+
+    assert_eq!(parse_number("42").unwrap(), 42);
+    assert!(parse_number("forty-two").is_err());
+
+    harness
+        .assert_recorded(
+            &TelemetryEventMatcher::event("parse_number")
+                .with_tag("value", "42")
+                .with_success(),
+        )
+        .await;
+
+    harness
+        .assert_recorded(
+            &TelemetryEventMatcher::event("parse_number")
+                .with_tag("value", "forty-two")
+                .with_fail_message("invalid digit found in string"),
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn test_async_function_err_sets_fail_result() {
+    let harness = TelemetryTestHarness::install().await;
+    let process_id = my_telemetry::generate_process_id();
+    let ctx = MyTelemetryContext::Single(process_id);
+
+    assert_eq!(load_order(&ctx, 1).await, Ok(1));
+    assert!(load_order(&ctx, -1).await.is_err());
+
+    let events = harness
+        .find(&TelemetryEventMatcher::event("load-order").with_process_id(process_id))
+        .await;
+    assert_eq!(events.len(), 2);
+
+    harness
+        .assert_recorded(&TelemetryEventMatcher::event("load-order").with_success())
+        .await;
+
+    harness
+        .assert_recorded(
+            &TelemetryEventMatcher::event("load-order").with_fail_message("Order -1 is not found"),
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn test_impl_trait_and_generic_functions() {
+    let harness = TelemetryTestHarness::install().await;
+
+    assert_eq!(get_even_numbers(5).collect::<Vec<_>>(), vec![0, 2, 4]);
+    assert_eq!(get_odd_numbers(5).unwrap().collect::<Vec<_>>(), vec![1, 3]);
+    assert!(get_odd_numbers(0).is_err());
+    assert_eq!(get_first(&["a", "b"]), Some("a"));
+    assert_eq!(get_first::<i32>(&[]), None);
+
+    harness
+        .assert_recorded(&TelemetryEventMatcher::event("get_even_numbers").with_success())
+        .await;
+
+    harness
+        .assert_recorded(&TelemetryEventMatcher::event("get_odd_numbers").with_success())
+        .await;
+
+    harness
+        .assert_recorded(
+            &TelemetryEventMatcher::event("get_odd_numbers").with_fail_message("Count is zero"),
+        )
+        .await;
+
+    let events = harness
+        .find(&TelemetryEventMatcher::event("get_first").with_success())
+        .await;
+    assert_eq!(events.len(), 2);
+}
+
+#[tokio::test]
+async fn test_methods_with_self() {
+    let harness = TelemetryTestHarness::install().await;
+    let repository = OrdersRepository {
+        prefix: "order".to_string(),
+    };
+
+    assert_eq!(repository.get_order_name(1), "order-1");
+    assert_eq!(repository.get_order_name_async(2).await, "order-2");
+
+    harness
+        .assert_recorded(&TelemetryEventMatcher::event("get_order_name").with_success())
+        .await;
+
+    harness
+        .assert_recorded(&TelemetryEventMatcher::event("get_order_name_async").with_success())
+        .await;
+}
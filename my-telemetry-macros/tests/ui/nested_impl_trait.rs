@@ -0,0 +1,24 @@
+use std::fmt::Display;
+
+use my_telemetry::track;
+
+#[track]
+fn get_numbers(count: usize) -> Result<impl Iterator<Item = usize>, String> {
+    Ok(0..count)
+}
+
+#[track]
+fn get_name(id: i32) -> Option<Box<impl Display>> {
+    Some(Box::new(id))
+}
+
+#[track]
+async fn get_names(count: usize) -> Result<Vec<impl Display>, String> {
+    Ok((0..count).collect())
+}
+
+fn main() {
+    let _ = get_numbers(1);
+    let _ = get_name(1);
+    let _ = get_names(1);
+}
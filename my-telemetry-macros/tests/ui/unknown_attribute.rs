@@ -0,0 +1,10 @@
+use my_telemetry::track;
+
+#[track(level = "info")]
+fn load_order(order_id: i32) -> i32 {
+    order_id
+}
+
+fn main() {
+    load_order(1);
+}
@@ -0,0 +1,10 @@
+use my_telemetry::track;
+
+#[track(tags(order_id, client_id))]
+fn load_order(order_id: i32) -> i32 {
+    order_id
+}
+
+fn main() {
+    load_order(1);
+}
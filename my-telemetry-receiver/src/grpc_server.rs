@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::{
+    writer_grpc::{
+        telemetry_writer_server::TelemetryWriter, MetricGrpcKind, TelemetryGrpcEvent,
+        TelemetryGrpcLogRecord, TelemetryGrpcMetric,
+    },
+    ReceivedMetricKind, ReceivedTelemetryEvent, ReceivedTelemetryLogRecord,
+    ReceivedTelemetryMetric, ReceivedVia, TelemetryReceiverStorage,
+};
+
+pub struct GrpcService {
+    storage: Arc<TelemetryReceiverStorage>,
+}
+
+impl GrpcService {
+    pub fn new(storage: Arc<TelemetryReceiverStorage>) -> Self {
+        Self { storage }
+    }
+}
+
+#[tonic::async_trait]
+impl TelemetryWriter for GrpcService {
+    async fn upload(
+        &self,
+        request: Request<Streaming<TelemetryGrpcEvent>>,
+    ) -> Result<Response<()>, Status> {
+        let mut stream = request.into_inner();
+
+        let mut events = Vec::new();
+
+        while let Some(item) = stream.message().await? {
+            events.push(ReceivedTelemetryEvent {
+                received_via: ReceivedVia::Grpc,
+                service_name: item.service_name,
+                process_id: item.process_id,
+                started: item.started_at,
+                finished: item.finished_at,
+                data: item.event_data,
+                success: item.success,
+                fail: item.fail,
+                tags: item
+                    .tags
+                    .into_iter()
+                    .map(|tag| (tag.key, tag.value))
+                    .collect(),
+                span_id: item.span_id,
+                parent_span_id: item.parent_span_id,
+            });
+        }
+
+        self.storage.add(events).await;
+
+        Ok(Response::new(()))
+    }
+
+    async fn upload_metrics(
+        &self,
+        request: Request<Streaming<TelemetryGrpcMetric>>,
+    ) -> Result<Response<()>, Status> {
+        let mut stream = request.into_inner();
+
+        let mut metrics = Vec::new();
+
+        while let Some(item) = stream.message().await? {
+            let kind = match item.kind() {
+                MetricGrpcKind::Counter => ReceivedMetricKind::Counter,
+                MetricGrpcKind::Gauge => ReceivedMetricKind::Gauge,
+                MetricGrpcKind::Histogram => ReceivedMetricKind::Histogram,
+            };
+
+            metrics.push(ReceivedTelemetryMetric {
+                received_via: ReceivedVia::Grpc,
+                service_name: item.service_name,
+                name: item.name,
+                kind,
+                tags: item
+                    .tags
+                    .into_iter()
+                    .map(|tag| (tag.key, tag.value))
+                    .collect(),
+                started: item.started_at,
+                updated: item.updated_at,
+                value: item.value,
+                count: item.count,
+                sum: item.sum,
+                min: item.min,
+                max: item.max,
+                bucket_bounds: item.bucket_bounds,
+                bucket_counts: item.bucket_counts,
+            });
+        }
+
+        self.storage.add_metrics(metrics).await;
+
+        Ok(Response::new(()))
+    }
+
+    async fn upload_logs(
+        &self,
+        request: Request<Streaming<TelemetryGrpcLogRecord>>,
+    ) -> Result<Response<()>, Status> {
+        let mut stream = request.into_inner();
+
+        let mut log_records = Vec::new();
+
+        while let Some(item) = stream.message().await? {
+            log_records.push(ReceivedTelemetryLogRecord {
+                received_via: ReceivedVia::Grpc,
+                service_name: item.service_name,
+                process_id: item.process_id,
+                span_id: item.span_id,
+                timestamp: item.timestamp,
+                level: item.level,
+                message: item.message,
+                tags: item
+                    .tags
+                    .into_iter()
+                    .map(|tag| (tag.key, tag.value))
+                    .collect(),
+            });
+        }
+
+        self.storage.add_log_records(log_records).await;
+
+        Ok(Response::new(()))
+    }
+
+    async fn ping(&self, _request: Request<()>) -> Result<Response<()>, Status> {
+        self.storage.add_ping().await;
+        Ok(Response::new(()))
+    }
+}
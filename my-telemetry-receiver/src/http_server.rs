@@ -0,0 +1,240 @@
+use std::{io::Read, sync::Arc};
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use serde::Deserialize;
+
+use crate::{
+    ReceivedMetricKind, ReceivedTelemetryEvent, ReceivedTelemetryLogRecord,
+    ReceivedTelemetryMetric, ReceivedVia, TelemetryReceiverStorage,
+};
+
+pub fn create_router(storage: Arc<TelemetryReceiverStorage>) -> Router {
+    Router::new()
+        .route("/api/add", post(add_events))
+        .route("/api/metrics", post(add_metrics))
+        .route("/api/logs", post(add_log_records))
+        .route("/v1/traces", post(crate::otlp_server::add_otlp_traces))
+        .with_state(storage)
+}
+
+async fn add_events(
+    State(storage): State<Arc<TelemetryReceiverStorage>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let content_encoding = headers
+        .get("Content-Encoding")
+        .and_then(|itm| itm.to_str().ok());
+
+    let body = match decompress(content_encoding, body.as_ref()) {
+        Ok(body) => body,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    let models: Vec<TelemetryHttpModel> = match serde_json::from_slice(body.as_slice()) {
+        Ok(models) => models,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    let events = models
+        .into_iter()
+        .map(|model| ReceivedTelemetryEvent {
+            received_via: ReceivedVia::Http,
+            service_name: model.service_name,
+            process_id: model.process_id,
+            started: model.started,
+            finished: model.ended,
+            data: model.event_data,
+            success: model.success,
+            fail: model.fail,
+            tags: model
+                .tags
+                .unwrap_or_default()
+                .into_iter()
+                .map(|tag| (tag.key, tag.value))
+                .collect(),
+            span_id: model.span_id,
+            parent_span_id: model.parent_span_id,
+        })
+        .collect();
+
+    storage.add(events).await;
+
+    StatusCode::OK
+}
+
+async fn add_metrics(
+    State(storage): State<Arc<TelemetryReceiverStorage>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let content_encoding = headers
+        .get("Content-Encoding")
+        .and_then(|itm| itm.to_str().ok());
+
+    let body = match decompress(content_encoding, body.as_ref()) {
+        Ok(body) => body,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    let models: Vec<TelemetryMetricHttpModel> = match serde_json::from_slice(body.as_slice()) {
+        Ok(models) => models,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    let mut metrics = Vec::with_capacity(models.len());
+
+    for model in models {
+        let kind = match model.kind.as_str() {
+            "counter" => ReceivedMetricKind::Counter,
+            "gauge" => ReceivedMetricKind::Gauge,
+            "histogram" => ReceivedMetricKind::Histogram,
+            _ => return StatusCode::BAD_REQUEST,
+        };
+
+        metrics.push(ReceivedTelemetryMetric {
+            received_via: ReceivedVia::Http,
+            service_name: model.service_name,
+            name: model.name,
+            kind,
+            tags: model
+                .tags
+                .into_iter()
+                .map(|tag| (tag.key, tag.value))
+                .collect(),
+            started: model.started,
+            updated: model.updated,
+            value: model.value.unwrap_or_default(),
+            count: model.count.unwrap_or_default(),
+            sum: model.sum.unwrap_or_default(),
+            min: model.min.unwrap_or_default(),
+            max: model.max.unwrap_or_default(),
+            bucket_bounds: model.bucket_bounds.unwrap_or_default(),
+            bucket_counts: model.bucket_counts.unwrap_or_default(),
+        });
+    }
+
+    storage.add_metrics(metrics).await;
+
+    StatusCode::OK
+}
+
+async fn add_log_records(
+    State(storage): State<Arc<TelemetryReceiverStorage>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let content_encoding = headers
+        .get("Content-Encoding")
+        .and_then(|itm| itm.to_str().ok());
+
+    let body = match decompress(content_encoding, body.as_ref()) {
+        Ok(body) => body,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    let models: Vec<TelemetryLogRecordHttpModel> = match serde_json::from_slice(body.as_slice()) {
+        Ok(models) => models,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    let log_records = models
+        .into_iter()
+        .map(|model| ReceivedTelemetryLogRecord {
+            received_via: ReceivedVia::Http,
+            service_name: model.service_name,
+            process_id: model.process_id,
+            span_id: model.span_id,
+            timestamp: model.timestamp,
+            level: model.level,
+            message: model.message,
+            tags: model
+                .tags
+                .unwrap_or_default()
+                .into_iter()
+                .map(|tag| (tag.key, tag.value))
+                .collect(),
+        })
+        .collect();
+
+    storage.add_log_records(log_records).await;
+
+    StatusCode::OK
+}
+
+pub fn decompress(content_encoding: Option<&str>, body: &[u8]) -> std::io::Result<Vec<u8>> {
+    match content_encoding {
+        Some("gzip") => {
+            let mut result = Vec::new();
+            flate2::read::GzDecoder::new(body).read_to_end(&mut result)?;
+            Ok(result)
+        }
+        Some("zstd") => zstd::decode_all(body),
+        _ => Ok(body.to_vec()),
+    }
+}
+
+#[derive(Deserialize)]
+struct TelemetryHttpModel {
+    #[serde(rename = "processId")]
+    process_id: i64,
+    started: i64,
+    ended: i64,
+    #[serde(rename = "serviceName")]
+    service_name: String,
+    #[serde(rename = "eventData")]
+    event_data: String,
+    success: Option<String>,
+    fail: Option<String>,
+    tags: Option<Vec<TelemetryHttpTag>>,
+    #[serde(rename = "spanId")]
+    span_id: Option<i64>,
+    #[serde(rename = "parentSpanId")]
+    parent_span_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct TelemetryHttpTag {
+    key: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct TelemetryMetricHttpModel {
+    #[serde(rename = "serviceName")]
+    service_name: String,
+    name: String,
+    kind: String,
+    tags: Vec<TelemetryHttpTag>,
+    started: i64,
+    updated: i64,
+    value: Option<f64>,
+    count: Option<u64>,
+    sum: Option<f64>,
+    min: Option<f64>,
+    max: Option<f64>,
+    #[serde(rename = "bucketBounds")]
+    bucket_bounds: Option<Vec<f64>>,
+    #[serde(rename = "bucketCounts")]
+    bucket_counts: Option<Vec<u64>>,
+}
+
+#[derive(Deserialize)]
+struct TelemetryLogRecordHttpModel {
+    #[serde(rename = "processId")]
+    process_id: i64,
+    #[serde(rename = "spanId")]
+    span_id: Option<i64>,
+    timestamp: i64,
+    #[serde(rename = "serviceName")]
+    service_name: String,
+    level: String,
+    message: String,
+    tags: Option<Vec<TelemetryHttpTag>>,
+}
@@ -0,0 +1,45 @@
+mod grpc_server;
+mod http_server;
+mod otlp_server;
+mod received_event;
+mod storage;
+mod telemetry_receiver;
+pub use received_event::*;
+pub use storage::TelemetryReceiverStorage;
+pub use telemetry_receiver::TelemetryReceiver;
+
+mod writer_grpc {
+    tonic::include_proto!("writer");
+}
+
+pub mod otlp_proto {
+    pub mod opentelemetry {
+        pub mod proto {
+            pub mod common {
+                pub mod v1 {
+                    tonic::include_proto!("opentelemetry.proto.common.v1");
+                }
+            }
+
+            pub mod resource {
+                pub mod v1 {
+                    tonic::include_proto!("opentelemetry.proto.resource.v1");
+                }
+            }
+
+            pub mod trace {
+                pub mod v1 {
+                    tonic::include_proto!("opentelemetry.proto.trace.v1");
+                }
+            }
+
+            pub mod collector {
+                pub mod trace {
+                    pub mod v1 {
+                        tonic::include_proto!("opentelemetry.proto.collector.trace.v1");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,325 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+};
+use prost::Message;
+use serde::Deserialize;
+use tonic::{Request, Response, Status};
+
+use crate::{
+    otlp_proto::opentelemetry::proto::{
+        collector::trace::v1::{
+            trace_service_server::TraceService, ExportTraceServiceRequest,
+            ExportTraceServiceResponse,
+        },
+        common::v1::{any_value, AnyValue, KeyValue},
+        resource::v1::Resource,
+        trace::v1::{status, ResourceSpans, ScopeSpans, Span, Status as SpanStatus},
+    },
+    ReceivedTelemetryEvent, ReceivedVia, TelemetryReceiverStorage,
+};
+
+const SERVICE_NAME_ATTRIBUTE: &str = "service.name";
+const SUCCESS_ATTRIBUTE: &str = "success";
+
+pub struct OtlpGrpcService {
+    storage: Arc<TelemetryReceiverStorage>,
+}
+
+impl OtlpGrpcService {
+    pub fn new(storage: Arc<TelemetryReceiverStorage>) -> Self {
+        Self { storage }
+    }
+}
+
+#[tonic::async_trait]
+impl TraceService for OtlpGrpcService {
+    async fn export(
+        &self,
+        request: Request<ExportTraceServiceRequest>,
+    ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+        let events = to_received_events(request.into_inner(), ReceivedVia::OtlpGrpc);
+        self.storage.add(events).await;
+
+        Ok(Response::new(ExportTraceServiceResponse {
+            partial_success: None,
+        }))
+    }
+}
+
+pub async fn add_otlp_traces(
+    State(storage): State<Arc<TelemetryReceiverStorage>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let content_encoding = headers
+        .get("Content-Encoding")
+        .and_then(|itm| itm.to_str().ok());
+
+    let body = match crate::http_server::decompress(content_encoding, body.as_ref()) {
+        Ok(body) => body,
+        Err(_) => return StatusCode::BAD_REQUEST,
+    };
+
+    let content_type = headers
+        .get("Content-Type")
+        .and_then(|itm| itm.to_str().ok())
+        .unwrap_or_default();
+
+    let request = if content_type.starts_with("application/json") {
+        match serde_json::from_slice::<OtlpJsonRequest>(body.as_slice()) {
+            Ok(model) => model.into_request(),
+            Err(_) => return StatusCode::BAD_REQUEST,
+        }
+    } else {
+        match ExportTraceServiceRequest::decode(body.as_slice()) {
+            Ok(request) => Some(request),
+            Err(_) => return StatusCode::BAD_REQUEST,
+        }
+    };
+
+    let request = match request {
+        Some(request) => request,
+        None => return StatusCode::BAD_REQUEST,
+    };
+
+    storage
+        .add(to_received_events(request, ReceivedVia::OtlpHttp))
+        .await;
+
+    StatusCode::OK
+}
+
+fn to_received_events(
+    request: ExportTraceServiceRequest,
+    received_via: ReceivedVia,
+) -> Vec<ReceivedTelemetryEvent> {
+    let mut result = Vec::new();
+
+    for resource_spans in request.resource_spans {
+        let service_name = resource_spans
+            .resource
+            .as_ref()
+            .and_then(|resource| get_string_attribute(&resource.attributes, SERVICE_NAME_ATTRIBUTE))
+            .unwrap_or_default();
+
+        for scope_spans in resource_spans.scope_spans {
+            for span in scope_spans.spans {
+                result.push(to_received_event(span, service_name.as_str(), received_via));
+            }
+        }
+    }
+
+    result
+}
+
+// Trace id carries the process id in the lower 64 bits
+fn to_received_event(
+    span: Span,
+    service_name: &str,
+    received_via: ReceivedVia,
+) -> ReceivedTelemetryEvent {
+    let fail = match &span.status {
+        Some(status) if status.code == status::StatusCode::Error as i32 => {
+            Some(status.message.clone())
+        }
+        _ => None,
+    };
+
+    let mut success = None;
+    let mut tags = Vec::new();
+
+    for attribute in &span.attributes {
+        let Some(value) = get_string_value(attribute) else {
+            continue;
+        };
+
+        if attribute.key == SUCCESS_ATTRIBUTE {
+            success = Some(value.to_string());
+        } else {
+            tags.push((attribute.key.clone(), value.to_string()));
+        }
+    }
+
+    ReceivedTelemetryEvent {
+        received_via,
+        service_name: service_name.to_string(),
+        process_id: bytes_to_u128(&span.trace_id) as u64 as i64,
+        started: (span.start_time_unix_nano / 1000) as i64,
+        finished: (span.end_time_unix_nano / 1000) as i64,
+        data: span.name,
+        success,
+        fail,
+        tags,
+        span_id: Some(bytes_to_u128(&span.span_id) as u64 as i64),
+        parent_span_id: if span.parent_span_id.is_empty() {
+            None
+        } else {
+            Some(bytes_to_u128(&span.parent_span_id) as u64 as i64)
+        },
+    }
+}
+
+fn get_string_attribute(attributes: &[KeyValue], key: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.key == key)
+        .and_then(get_string_value)
+        .map(|value| value.to_string())
+}
+
+fn get_string_value(attribute: &KeyValue) -> Option<&str> {
+    match attribute.value.as_ref()?.value.as_ref()? {
+        any_value::Value::StringValue(value) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+fn bytes_to_u128(bytes: &[u8]) -> u128 {
+    bytes
+        .iter()
+        .fold(0u128, |result, b| (result << 8) | *b as u128)
+}
+
+// Odd length gives None: the last pair is not complete
+fn hex_to_bytes(value: &str) -> Option<Vec<u8>> {
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// OTLP/JSON encodes trace and span ids as hex strings and 64 bit integers as strings
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OtlpJsonRequest {
+    #[serde(default)]
+    resource_spans: Vec<OtlpJsonResourceSpans>,
+}
+
+impl OtlpJsonRequest {
+    fn into_request(self) -> Option<ExportTraceServiceRequest> {
+        let mut resource_spans = Vec::with_capacity(self.resource_spans.len());
+
+        for model in self.resource_spans {
+            let mut scope_spans = Vec::with_capacity(model.scope_spans.len());
+
+            for scope_model in model.scope_spans {
+                let mut spans = Vec::with_capacity(scope_model.spans.len());
+                for span in scope_model.spans {
+                    spans.push(span.into_span()?);
+                }
+
+                scope_spans.push(ScopeSpans {
+                    spans,
+                    ..Default::default()
+                });
+            }
+
+            resource_spans.push(ResourceSpans {
+                resource: model.resource.map(|resource| Resource {
+                    attributes: into_key_values(resource.attributes),
+                    dropped_attributes_count: 0,
+                }),
+                scope_spans,
+                schema_url: String::new(),
+            });
+        }
+
+        Some(ExportTraceServiceRequest { resource_spans })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OtlpJsonResourceSpans {
+    resource: Option<OtlpJsonResource>,
+    #[serde(default)]
+    scope_spans: Vec<OtlpJsonScopeSpans>,
+}
+
+#[derive(Deserialize)]
+struct OtlpJsonResource {
+    #[serde(default)]
+    attributes: Vec<OtlpJsonKeyValue>,
+}
+
+#[derive(Deserialize)]
+struct OtlpJsonScopeSpans {
+    #[serde(default)]
+    spans: Vec<OtlpJsonSpan>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OtlpJsonSpan {
+    trace_id: String,
+    span_id: String,
+    parent_span_id: Option<String>,
+    name: String,
+    start_time_unix_nano: String,
+    end_time_unix_nano: String,
+    #[serde(default)]
+    attributes: Vec<OtlpJsonKeyValue>,
+    status: Option<OtlpJsonStatus>,
+}
+
+impl OtlpJsonSpan {
+    fn into_span(self) -> Option<Span> {
+        Some(Span {
+            trace_id: hex_to_bytes(self.trace_id.as_str())?,
+            span_id: hex_to_bytes(self.span_id.as_str())?,
+            parent_span_id: match self.parent_span_id {
+                Some(parent_span_id) => hex_to_bytes(parent_span_id.as_str())?,
+                None => Vec::new(),
+            },
+            name: self.name,
+            start_time_unix_nano: self.start_time_unix_nano.parse().ok()?,
+            end_time_unix_nano: self.end_time_unix_nano.parse().ok()?,
+            attributes: into_key_values(self.attributes),
+            status: self.status.map(|status| SpanStatus {
+                message: status.message,
+                code: status.code,
+            }),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct OtlpJsonKeyValue {
+    key: String,
+    value: OtlpJsonAnyValue,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OtlpJsonAnyValue {
+    string_value: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OtlpJsonStatus {
+    #[serde(default)]
+    code: i32,
+    #[serde(default)]
+    message: String,
+}
+
+fn into_key_values(attributes: Vec<OtlpJsonKeyValue>) -> Vec<KeyValue> {
+    attributes
+        .into_iter()
+        .filter_map(|attribute| {
+            let value = attribute.value.string_value?;
+            Some(KeyValue {
+                key: attribute.key,
+                value: Some(AnyValue {
+                    value: Some(any_value::Value::StringValue(value)),
+                }),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,90 @@
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceivedVia {
+    Grpc,
+    Http,
+    OtlpGrpc,
+    OtlpHttp,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReceivedTelemetryEvent {
+    pub received_via: ReceivedVia,
+    pub service_name: String,
+    pub process_id: i64,
+    pub started: i64,
+    pub finished: i64,
+    pub data: String,
+    pub success: Option<String>,
+    pub fail: Option<String>,
+    pub tags: Vec<(String, String)>,
+    pub span_id: Option<i64>,
+    pub parent_span_id: Option<i64>,
+}
+
+impl ReceivedTelemetryEvent {
+    pub fn get_tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_key, _)| tag_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn is_fail(&self) -> bool {
+        self.fail.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceivedMetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReceivedTelemetryMetric {
+    pub received_via: ReceivedVia,
+    pub service_name: String,
+    pub name: String,
+    pub kind: ReceivedMetricKind,
+    pub tags: Vec<(String, String)>,
+    pub started: i64,
+    pub updated: i64,
+    pub value: f64,
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub bucket_bounds: Vec<f64>,
+    pub bucket_counts: Vec<u64>,
+}
+
+impl ReceivedTelemetryMetric {
+    pub fn get_tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_key, _)| tag_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReceivedTelemetryLogRecord {
+    pub received_via: ReceivedVia,
+    pub service_name: String,
+    pub process_id: i64,
+    pub span_id: Option<i64>,
+    pub timestamp: i64,
+    pub level: String,
+    pub message: String,
+    pub tags: Vec<(String, String)>,
+}
+
+impl ReceivedTelemetryLogRecord {
+    pub fn get_tag(&self, key: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag_key, _)| tag_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use tokio::sync::{Mutex, Notify};
+
+use crate::{ReceivedTelemetryEvent, ReceivedTelemetryLogRecord, ReceivedTelemetryMetric};
+
+pub struct TelemetryReceiverStorage {
+    events: Mutex<Vec<ReceivedTelemetryEvent>>,
+    metrics: Mutex<Vec<ReceivedTelemetryMetric>>,
+    log_records: Mutex<Vec<ReceivedTelemetryLogRecord>>,
+    pings: Mutex<usize>,
+    new_events: Notify,
+}
+
+impl TelemetryReceiverStorage {
+    pub fn new() -> Self {
+        Self {
+            events: Mutex::new(Vec::new()),
+            metrics: Mutex::new(Vec::new()),
+            log_records: Mutex::new(Vec::new()),
+            pings: Mutex::new(0),
+            new_events: Notify::new(),
+        }
+    }
+
+    pub async fn add(&self, events: Vec<ReceivedTelemetryEvent>) {
+        let mut write_access = self.events.lock().await;
+        write_access.extend(events);
+        self.new_events.notify_waiters();
+    }
+
+    pub async fn add_metrics(&self, metrics: Vec<ReceivedTelemetryMetric>) {
+        let mut write_access = self.metrics.lock().await;
+        write_access.extend(metrics);
+        self.new_events.notify_waiters();
+    }
+
+    pub async fn add_log_records(&self, log_records: Vec<ReceivedTelemetryLogRecord>) {
+        let mut write_access = self.log_records.lock().await;
+        write_access.extend(log_records);
+        self.new_events.notify_waiters();
+    }
+
+    pub async fn add_ping(&self) {
+        let mut write_access = self.pings.lock().await;
+        *write_access += 1;
+    }
+
+    pub async fn get_pings(&self) -> usize {
+        *self.pings.lock().await
+    }
+
+    pub async fn get_events(&self) -> Vec<ReceivedTelemetryEvent> {
+        self.events.lock().await.clone()
+    }
+
+    // Every export sends the cumulative snapshot, so the same metric can be received several times
+    pub async fn get_metrics(&self) -> Vec<ReceivedTelemetryMetric> {
+        self.metrics.lock().await.clone()
+    }
+
+    pub async fn get_last_metric(&self, name: &str) -> Option<ReceivedTelemetryMetric> {
+        let read_access = self.metrics.lock().await;
+        read_access
+            .iter()
+            .rev()
+            .find(|itm| itm.name == name)
+            .cloned()
+    }
+
+    pub async fn get_log_records(&self) -> Vec<ReceivedTelemetryLogRecord> {
+        self.log_records.lock().await.clone()
+    }
+
+    pub async fn get_log_records_by_process_id(
+        &self,
+        process_id: i64,
+    ) -> Vec<ReceivedTelemetryLogRecord> {
+        let read_access = self.log_records.lock().await;
+        read_access
+            .iter()
+            .filter(|itm| itm.process_id == process_id)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn get_events_count(&self) -> usize {
+        self.events.lock().await.len()
+    }
+
+    pub async fn get_events_by_process_id(&self, process_id: i64) -> Vec<ReceivedTelemetryEvent> {
+        self.filter(|itm| itm.process_id == process_id).await
+    }
+
+    pub async fn get_events_by_name(&self, event_name: &str) -> Vec<ReceivedTelemetryEvent> {
+        self.filter(|itm| itm.data == event_name).await
+    }
+
+    pub async fn filter(
+        &self,
+        predicate: impl Fn(&ReceivedTelemetryEvent) -> bool,
+    ) -> Vec<ReceivedTelemetryEvent> {
+        let read_access = self.events.lock().await;
+        read_access
+            .iter()
+            .filter(|itm| predicate(itm))
+            .cloned()
+            .collect()
+    }
+
+    // Returns false if the amount of events was not reached before the timeout
+    pub async fn wait_for_events(&self, amount: usize, timeout: Duration) -> bool {
+        let wait = async {
+            loop {
+                let notified = self.new_events.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                if self.get_events_count().await >= amount {
+                    return;
+                }
+
+                notified.await;
+            }
+        };
+
+        tokio::time::timeout(timeout, wait).await.is_ok()
+    }
+
+    pub async fn clear(&self) {
+        self.events.lock().await.clear();
+        self.metrics.lock().await.clear();
+        self.log_records.lock().await.clear();
+    }
+}
+
+impl Default for TelemetryReceiverStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,91 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use tokio::{net::TcpListener, task::JoinHandle};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::codec::CompressionEncoding;
+
+use crate::{
+    grpc_server::GrpcService,
+    otlp_proto::opentelemetry::proto::collector::trace::v1::trace_service_server::TraceServiceServer,
+    otlp_server::OtlpGrpcService, writer_grpc::telemetry_writer_server::TelemetryWriterServer,
+    TelemetryReceiverStorage,
+};
+
+pub struct TelemetryReceiver {
+    pub storage: Arc<TelemetryReceiverStorage>,
+    grpc_addr: SocketAddr,
+    http_addr: SocketAddr,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl TelemetryReceiver {
+    pub async fn start_on_localhost() -> std::io::Result<Self> {
+        Self::start("127.0.0.1:0", "127.0.0.1:0").await
+    }
+
+    pub async fn start(grpc_addr: &str, http_addr: &str) -> std::io::Result<Self> {
+        let storage = Arc::new(TelemetryReceiverStorage::new());
+
+        let grpc_listener = TcpListener::bind(grpc_addr).await?;
+        let grpc_addr = grpc_listener.local_addr()?;
+
+        let http_listener = TcpListener::bind(http_addr).await?;
+        let http_addr = http_listener.local_addr()?;
+
+        let grpc_service = TelemetryWriterServer::new(GrpcService::new(storage.clone()))
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd);
+
+        // OTLP traces are served on the same ports: gRPC TraceService and HTTP /v1/traces
+        let otlp_service = TraceServiceServer::new(OtlpGrpcService::new(storage.clone()))
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd);
+
+        let grpc_handle = tokio::spawn(async move {
+            let result = tonic::transport::Server::builder()
+                .add_service(grpc_service)
+                .add_service(otlp_service)
+                .serve_with_incoming(TcpListenerStream::new(grpc_listener))
+                .await;
+
+            if let Err(err) = result {
+                println!("Telemetry receiver gRPC server is stopped: {:?}", err);
+            }
+        });
+
+        let router = crate::http_server::create_router(storage.clone());
+
+        let http_handle = tokio::spawn(async move {
+            if let Err(err) = axum::serve(http_listener, router).await {
+                println!("Telemetry receiver HTTP server is stopped: {:?}", err);
+            }
+        });
+
+        Ok(Self {
+            storage,
+            grpc_addr,
+            http_addr,
+            handles: vec![grpc_handle, http_handle],
+        })
+    }
+
+    pub fn get_grpc_url(&self) -> String {
+        format!("http://{}", self.grpc_addr)
+    }
+
+    pub fn get_http_url(&self) -> String {
+        format!("http://{}", self.http_addr)
+    }
+
+    pub fn stop(&mut self) {
+        for handle in self.handles.drain(..) {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for TelemetryReceiver {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use my_telemetry_core::{TelemetryEvent, TelemetryEventTag};
+use my_telemetry_receiver::{ReceivedVia, TelemetryReceiver};
+use my_telemetry_writer::{
+    MyTelemetrySettings, OtlpProtocol, OtlpTelemetryExporter, TelemetryExporter,
+};
+
+struct TestSettings {
+    url: String,
+}
+
+#[async_trait::async_trait]
+impl MyTelemetrySettings for TestSettings {
+    async fn get_telemetry_url(&self) -> Option<String> {
+        Some(self.url.clone())
+    }
+}
+
+fn create_events() -> Vec<TelemetryEvent> {
+    vec![
+        TelemetryEvent {
+            process_id: 1_700_000_000_000_000,
+            started: 1_700_000_000_000_000,
+            finished: 1_700_000_000_000_100,
+            data: "ok-event".to_string(),
+            success: Some("done".to_string()),
+            fail: None,
+            tags: Some(vec![TelemetryEventTag {
+                key: "client_id".to_string(),
+                value: "client".to_string(),
+            }]),
+            span_id: Some(1_700_000_000_000_001),
+            parent_span_id: Some(1_700_000_000_000_000),
+        },
+        TelemetryEvent {
+            process_id: 1_700_000_000_000_000,
+            started: 1_700_000_000_000_010,
+            finished: 1_700_000_000_000_020,
+            data: "fail-event".to_string(),
+            success: None,
+            fail: Some("error".to_string()),
+            tags: None,
+            span_id: None,
+            parent_span_id: None,
+        },
+    ]
+}
+
+async fn check_round_trip(protocol: OtlpProtocol, expected_via: ReceivedVia) {
+    let receiver = TelemetryReceiver::start_on_localhost().await.unwrap();
+
+    let url = match protocol {
+        OtlpProtocol::Grpc => receiver.get_grpc_url(),
+        _ => receiver.get_http_url(),
+    };
+
+    let exporter = OtlpTelemetryExporter::new("test-app", Arc::new(TestSettings { url }), protocol);
+
+    let events = create_events();
+    exporter.export(&events).await.unwrap();
+    exporter.export(&events).await.unwrap();
+
+    let received = receiver.storage.get_events().await;
+    assert_eq!(received.len(), 4);
+
+    for event in &received {
+        assert_eq!(event.received_via, expected_via);
+        assert_eq!(event.service_name, "test-app");
+        assert_eq!(event.process_id, 1_700_000_000_000_000);
+    }
+
+    let ok_event = &received[0];
+    assert_eq!(ok_event.data, "ok-event");
+    assert_eq!(ok_event.started, 1_700_000_000_000_000);
+    assert_eq!(ok_event.finished, 1_700_000_000_000_100);
+    assert_eq!(ok_event.success.as_deref(), Some("done"));
+    assert!(!ok_event.is_fail());
+    assert_eq!(ok_event.get_tag("client_id"), Some("client"));
+    assert_eq!(ok_event.span_id, Some(1_700_000_000_000_001));
+    assert_eq!(ok_event.parent_span_id, Some(1_700_000_000_000_000));
+
+    let fail_event = &received[1];
+    assert_eq!(fail_event.data, "fail-event");
+    assert_eq!(fail_event.fail.as_deref(), Some("error"));
+    assert_eq!(fail_event.parent_span_id, None);
+
+    // Span id of the event without one is the same on every export
+    assert!(fail_event.span_id.is_some());
+    assert_eq!(fail_event.span_id, received[3].span_id);
+}
+
+#[tokio::test]
+async fn test_otlp_grpc_round_trip() {
+    check_round_trip(OtlpProtocol::Grpc, ReceivedVia::OtlpGrpc).await;
+}
+
+#[tokio::test]
+async fn test_otlp_http_protobuf_round_trip() {
+    check_round_trip(OtlpProtocol::HttpProtobuf, ReceivedVia::OtlpHttp).await;
+}
+
+#[tokio::test]
+async fn test_otlp_http_json_round_trip() {
+    check_round_trip(OtlpProtocol::HttpJson, ReceivedVia::OtlpHttp).await;
+}
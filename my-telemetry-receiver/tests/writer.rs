@@ -0,0 +1,232 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use my_telemetry_core::{MyTelemetryContext, TelemetryEvent, TelemetryInterface};
+use my_telemetry_receiver::{ReceivedVia, TelemetryReceiver};
+use my_telemetry_writer::{
+    HttpTelemetryExporter, MyTelemetrySettings, MyTelemetryWriter, TelemetryCompression,
+    TelemetryRetrySettings,
+};
+use rust_extensions::{ApplicationStates, Logger};
+
+struct TestSettings {
+    url: String,
+    compression: TelemetryCompression,
+}
+
+#[async_trait::async_trait]
+impl MyTelemetrySettings for TestSettings {
+    async fn get_telemetry_url(&self) -> Option<String> {
+        Some(self.url.clone())
+    }
+
+    async fn get_compression(&self) -> TelemetryCompression {
+        self.compression
+    }
+}
+
+struct TestAppStates;
+
+impl ApplicationStates for TestAppStates {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        false
+    }
+}
+
+struct TestLogger;
+
+impl Logger for TestLogger {
+    fn write_info(&self, _: String, _: String, _: Option<HashMap<String, String>>) {}
+    fn write_warning(&self, _: String, _: String, _: Option<HashMap<String, String>>) {}
+    fn write_error(&self, _: String, _: String, _: Option<HashMap<String, String>>) {}
+    fn write_fatal_error(&self, _: String, _: String, _: Option<HashMap<String, String>>) {}
+    fn write_debug_info(&self, _: String, _: String, _: Option<HashMap<String, String>>) {}
+}
+
+async fn check_writer(
+    url: String,
+    compression: TelemetryCompression,
+    expected_via: ReceivedVia,
+    receiver: &TelemetryReceiver,
+) {
+    let telemetry_interface = Arc::new(TelemetryInterface::new());
+
+    let writer = MyTelemetryWriter::new("test-app", Arc::new(TestSettings { url, compression }))
+        .with_telemetry_interface(telemetry_interface.clone());
+
+    writer.start(Arc::new(TestAppStates), Arc::new(TestLogger));
+
+    let ctx = MyTelemetryContext::Single(1_700_000_000_000_000);
+
+    {
+        let mut tracker = ctx
+            .start_event_tracking("ok-event")
+            .with_telemetry_interface(telemetry_interface.clone())
+            .add_tag("client_id", "client");
+        tracker.set_ok_result("done");
+
+        let mut child = tracker.start_child("fail-event");
+        child.set_fail_result("error".to_string());
+    }
+
+    while telemetry_interface.get_pending_writes() > 0 {
+        tokio::task::yield_now().await;
+    }
+
+    let result = writer.shutdown(Duration::from_secs(5)).await;
+    assert_eq!(result.delivered_events, 2);
+    assert!(result.is_everything_delivered());
+
+    let events = receiver
+        .storage
+        .get_events_by_process_id(1_700_000_000_000_000)
+        .await;
+    assert_eq!(events.len(), 2);
+
+    for event in &events {
+        assert_eq!(event.received_via, expected_via);
+        assert_eq!(event.service_name, "test-app");
+    }
+
+    let ok_event = receiver.storage.get_events_by_name("ok-event").await;
+    assert_eq!(ok_event.len(), 1);
+    assert_eq!(ok_event[0].success.as_deref(), Some("done"));
+    assert_eq!(ok_event[0].get_tag("client_id"), Some("client"));
+
+    let fail_event = receiver.storage.get_events_by_name("fail-event").await;
+    assert_eq!(fail_event.len(), 1);
+    assert_eq!(fail_event[0].fail.as_deref(), Some("error"));
+    assert_eq!(fail_event[0].parent_span_id, ok_event[0].span_id);
+}
+
+#[tokio::test]
+async fn test_writer_to_grpc_receiver() {
+    let receiver = TelemetryReceiver::start_on_localhost().await.unwrap();
+    check_writer(
+        receiver.get_grpc_url(),
+        TelemetryCompression::None,
+        ReceivedVia::Grpc,
+        &receiver,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_writer_to_http_receiver() {
+    let receiver = TelemetryReceiver::start_on_localhost().await.unwrap();
+    check_writer(
+        receiver.get_http_url(),
+        TelemetryCompression::None,
+        ReceivedVia::Http,
+        &receiver,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_gzip_writer_to_grpc_receiver() {
+    let receiver = TelemetryReceiver::start_on_localhost().await.unwrap();
+    check_writer(
+        receiver.get_grpc_url(),
+        TelemetryCompression::Gzip,
+        ReceivedVia::Grpc,
+        &receiver,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_gzip_writer_to_http_receiver() {
+    let receiver = TelemetryReceiver::start_on_localhost().await.unwrap();
+    check_writer(
+        receiver.get_http_url(),
+        TelemetryCompression::Gzip,
+        ReceivedVia::Http,
+        &receiver,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_zstd_writer_to_http_receiver() {
+    let receiver = TelemetryReceiver::start_on_localhost().await.unwrap();
+    check_writer(
+        receiver.get_http_url(),
+        TelemetryCompression::Zstd,
+        ReceivedVia::Http,
+        &receiver,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_http_writer_does_not_deliver_to_bad_request() {
+    let requests = Arc::new(AtomicUsize::new(0));
+
+    let router = {
+        let requests = requests.clone();
+        axum::Router::new().fallback(move || {
+            let requests = requests.clone();
+            async move {
+                requests.fetch_add(1, Ordering::SeqCst);
+                axum::http::StatusCode::BAD_REQUEST
+            }
+        })
+    };
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let server = tokio::spawn(async move {
+        axum::serve(listener, router).await.unwrap();
+    });
+
+    let telemetry_interface = Arc::new(TelemetryInterface::new());
+
+    let settings = TestSettings {
+        url,
+        compression: TelemetryCompression::None,
+    };
+    let exporter = HttpTelemetryExporter::new("test-app", Arc::new(settings));
+    let writer = MyTelemetryWriter::new_with_exporter(Arc::new(exporter))
+        .with_telemetry_interface(telemetry_interface.clone())
+        .with_retry_settings(TelemetryRetrySettings {
+            max_attempts: 2,
+            initial_backoff: Duration::ZERO,
+            ..Default::default()
+        });
+
+    telemetry_interface
+        .write_telemetry_event(TelemetryEvent {
+            process_id: 1_700_000_000_000_001,
+            started: 0,
+            finished: 0,
+            data: "rejected-event".to_string(),
+            success: Some("done".to_string()),
+            fail: None,
+            tags: None,
+            span_id: None,
+            parent_span_id: None,
+        })
+        .await;
+
+    let result = writer.flush(Duration::from_secs(5)).await;
+    assert_eq!(result.not_delivered_events, 1);
+    assert_eq!(writer.get_events_to_retry(), 1);
+
+    writer.flush(Duration::from_secs(5)).await;
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    assert_eq!(writer.get_events_to_retry(), 0);
+    assert_eq!(writer.get_discarded_events(), 1);
+
+    server.abort();
+}
@@ -0,0 +1,37 @@
+use std::fmt::Debug;
+
+use tracing::field::{Field, Visit};
+
+pub const MESSAGE_FIELD: &str = "message";
+pub const MY_TELEMETRY_FIELD: &str = "my_telemetry";
+
+#[derive(Default)]
+pub struct FieldsVisitor {
+    pub fields: Vec<(&'static str, String)>,
+    pub message: Option<String>,
+    pub my_telemetry: Option<String>,
+}
+
+impl FieldsVisitor {
+    fn add(&mut self, field: &Field, value: String) {
+        match field.name() {
+            MESSAGE_FIELD => self.message = Some(value),
+            MY_TELEMETRY_FIELD => self.my_telemetry = Some(value),
+            name => self.fields.push((name, value)),
+        }
+    }
+}
+
+impl Visit for FieldsVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.add(field, value.to_string());
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.add(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.add(field, format!("{:?}", value));
+    }
+}
@@ -0,0 +1,5 @@
+mod fields_visitor;
+mod my_telemetry_layer;
+mod span_ext;
+pub use my_telemetry_layer::*;
+pub use span_ext::*;
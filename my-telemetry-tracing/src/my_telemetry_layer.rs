@@ -0,0 +1,251 @@
+use std::sync::Arc;
+
+use my_telemetry_core::{EventDurationTracker, MyTelemetryContext, TelemetryInterface};
+use tracing::{
+    span::{Attributes, Id, Record},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+use crate::fields_visitor::FieldsVisitor;
+
+pub struct MyTelemetryLayer {
+    fail_level: Level,
+    telemetry_interface: Option<Arc<TelemetryInterface>>,
+}
+
+impl MyTelemetryLayer {
+    pub fn new() -> Self {
+        Self {
+            fail_level: Level::ERROR,
+            telemetry_interface: None,
+        }
+    }
+
+    pub fn with_telemetry_interface(
+        mut self,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        self.telemetry_interface = Some(telemetry_interface);
+        self
+    }
+
+    // Events with this level or more severe mark the span as failed
+    pub fn with_fail_level(mut self, fail_level: Level) -> Self {
+        self.fail_level = fail_level;
+        self
+    }
+}
+
+impl Default for MyTelemetryLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for MyTelemetryLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let mut visitor = FieldsVisitor::default();
+        attrs.record(&mut visitor);
+
+        let event_name = span.name();
+
+        let my_telemetry = visitor
+            .my_telemetry
+            .as_ref()
+            .and_then(|itm| MyTelemetryContext::parse_from_string(itm.as_str()).ok());
+
+        let mut tracker = match my_telemetry {
+            Some(my_telemetry) => my_telemetry.start_event_tracking(event_name),
+            None => {
+                let parent_tracker = span.parent().and_then(|parent| {
+                    parent
+                        .extensions()
+                        .get::<EventDurationTracker>()
+                        .map(|itm| itm.start_child(event_name))
+                });
+
+                match parent_tracker {
+                    Some(tracker) => tracker,
+                    None => EventDurationTracker::start_process(
+                        event_name,
+                        None,
+                        self.telemetry_interface.clone(),
+                    ),
+                }
+            }
+        };
+
+        for (key, value) in visitor.fields {
+            tracker.add_tag_as_ref(key, value);
+        }
+
+        if let Some(telemetry_interface) = &self.telemetry_interface {
+            tracker.telemetry_interface = Some(telemetry_interface.clone());
+        }
+
+        span.extensions_mut().insert(tracker);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let mut visitor = FieldsVisitor::default();
+        values.record(&mut visitor);
+
+        let mut extensions = span.extensions_mut();
+
+        let tracker = match extensions.get_mut::<EventDurationTracker>() {
+            Some(tracker) => tracker,
+            None => return,
+        };
+
+        // my_telemetry recorded later moves the span to that process as the child of its root span
+        let my_telemetry = visitor
+            .my_telemetry
+            .as_ref()
+            .and_then(|itm| MyTelemetryContext::parse_from_string(itm.as_str()).ok());
+
+        if let Some(my_telemetry) = my_telemetry {
+            tracker.my_telemetry = my_telemetry;
+            tracker.parent_span_id = None;
+        }
+
+        for (key, value) in visitor.fields {
+            tracker.add_tag_as_ref(key, value);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if *event.metadata().level() > self.fail_level {
+            return;
+        }
+
+        let span = match ctx.event_span(event) {
+            Some(span) => span,
+            None => return,
+        };
+
+        let mut visitor = FieldsVisitor::default();
+        event.record(&mut visitor);
+
+        let fail = match visitor.message {
+            Some(message) => message,
+            None => event.metadata().name().to_string(),
+        };
+
+        let mut extensions = span.extensions_mut();
+
+        if let Some(tracker) = extensions.get_mut::<EventDurationTracker>() {
+            tracker.set_fail_result(fail);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+
+        // Dropping the tracker writes the event
+        let tracker = span.extensions_mut().remove::<EventDurationTracker>();
+        drop(tracker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use my_telemetry_core::{HeadSampler, TelemetryEventMatcher, TelemetryTestHarness};
+    use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+    use super::*;
+
+    fn create_subscriber(telemetry_interface: &Arc<TelemetryInterface>) -> impl Subscriber {
+        Registry::default()
+            .with(MyTelemetryLayer::new().with_telemetry_interface(telemetry_interface.clone()))
+    }
+
+    #[tokio::test]
+    async fn test_span_is_written_as_event() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let subscriber = create_subscriber(harness.get_telemetry_interface());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("process_order", order_id = "order-1");
+            let _enter = span.enter();
+            tracing::error!("order is not found");
+        });
+
+        harness
+            .assert_recorded(
+                &TelemetryEventMatcher::event("process_order")
+                    .with_tag("order_id", "order-1")
+                    .with_fail_message("order is not found"),
+            )
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_recorded_my_telemetry_sets_the_process() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let subscriber = create_subscriber(harness.get_telemetry_interface());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("handle", my_telemetry = tracing::field::Empty);
+            span.record("my_telemetry", "1700000000000000");
+        });
+
+        let events = harness
+            .find(&TelemetryEventMatcher::event("handle").with_process_id(1_700_000_000_000_000))
+            .await;
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].parent_span_id, Some(1_700_000_000_000_000));
+    }
+
+    #[tokio::test]
+    async fn test_root_span_is_sampled_by_the_interface() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        harness
+            .get_telemetry_interface()
+            .set_head_sampler(HeadSampler::new().with_ratio(0.0));
+        let subscriber = create_subscriber(harness.get_telemetry_interface());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let _span = tracing::info_span!("not_sampled");
+        });
+
+        harness
+            .assert_not_recorded(&TelemetryEventMatcher::event("not_sampled"))
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_span_closed_outside_of_runtime() {
+        let harness = TelemetryTestHarness::install_isolated().await;
+        let subscriber = create_subscriber(harness.get_telemetry_interface());
+
+        std::thread::spawn(move || {
+            tracing::subscriber::with_default(subscriber, || {
+                let _span = tracing::info_span!("no_runtime");
+            });
+        })
+        .join()
+        .unwrap();
+
+        harness
+            .assert_not_recorded(&TelemetryEventMatcher::event("no_runtime"))
+            .await;
+    }
+}
@@ -0,0 +1,34 @@
+use my_telemetry_core::{EventDurationTracker, MyTelemetryContext};
+use tracing_subscriber::{registry::LookupSpan, Registry};
+
+pub trait MyTelemetrySpanExt {
+    fn set_my_telemetry_context(&self, ctx: &MyTelemetryContext);
+    fn get_my_telemetry_context(&self) -> Option<MyTelemetryContext>;
+}
+
+impl MyTelemetrySpanExt for tracing::Span {
+    fn set_my_telemetry_context(&self, ctx: &MyTelemetryContext) {
+        self.with_subscriber(|(id, dispatch)| {
+            let registry = dispatch.downcast_ref::<Registry>()?;
+            let span = registry.span(id)?;
+            let mut extensions = span.extensions_mut();
+            let tracker = extensions.get_mut::<EventDurationTracker>()?;
+
+            // Span becomes the child of the process root span
+            tracker.my_telemetry = ctx.clone();
+            tracker.parent_span_id = None;
+            Some(())
+        });
+    }
+
+    fn get_my_telemetry_context(&self) -> Option<MyTelemetryContext> {
+        self.with_subscriber(|(id, dispatch)| {
+            let registry = dispatch.downcast_ref::<Registry>()?;
+            let span = registry.span(id)?;
+            let extensions = span.extensions();
+            let tracker = extensions.get::<EventDurationTracker>()?;
+            Some(tracker.my_telemetry.clone())
+        })
+        .flatten()
+    }
+}
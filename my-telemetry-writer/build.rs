@@ -1,6 +1,12 @@
 fn main() {
     tonic_prost_build::configure()
         .protoc_arg("--experimental_allow_proto3_optional")
-        .compile_protos(&["proto/TelemetryWriter.proto"], &["proto"])
+        .compile_protos(
+            &[
+                "proto/TelemetryWriter.proto",
+                "proto/opentelemetry/proto/collector/trace/v1/trace_service.proto",
+            ],
+            &["proto"],
+        )
         .unwrap();
 }
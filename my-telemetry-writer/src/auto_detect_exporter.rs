@@ -0,0 +1,153 @@
+use std::sync::Arc;
+
+use my_telemetry_core::{TelemetryEvent, TelemetryLogRecord, TelemetryMetric};
+
+use crate::{
+    settings::get_write_target,
+    write_mode::{WriteMode, WriteModeKeeper},
+    GrpcTelemetryExporter, HttpTelemetryExporter, MyTelemetrySettings, TelemetryCompression,
+    TelemetryExporter,
+};
+
+pub struct AutoDetectTelemetryExporter {
+    settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
+    write_mode: WriteModeKeeper,
+    grpc: GrpcTelemetryExporter,
+    http: HttpTelemetryExporter,
+}
+
+impl AutoDetectTelemetryExporter {
+    pub fn new(
+        app_name: impl Into<String>,
+        settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
+    ) -> Self {
+        let app_name: String = app_name.into();
+        Self {
+            grpc: GrpcTelemetryExporter::new(app_name.as_str(), settings.clone()),
+            http: HttpTelemetryExporter::new(app_name, settings.clone()),
+            write_mode: WriteModeKeeper::new(),
+            settings,
+        }
+    }
+
+    async fn detect_write_mode(&self, url: &str) {
+        if self.grpc.is_grpc(url).await {
+            self.write_mode.set_write_mode(WriteMode::Grpc);
+            return;
+        }
+
+        self.write_mode.set_write_mode(WriteMode::Http);
+    }
+
+    // Write mode is detected on the first write, so it is never Unknown in the result
+    async fn get_write_target(
+        &self,
+    ) -> Result<Option<(String, TelemetryCompression, WriteMode)>, String> {
+        let Some((url, compression)) = get_write_target(self.settings.as_ref()).await else {
+            return Ok(None);
+        };
+
+        if self.write_mode.get_write_mode().is_unknown() {
+            self.detect_write_mode(url.as_str()).await;
+        }
+
+        match self.write_mode.get_write_mode() {
+            WriteMode::Unknown => {
+                Err("Somehow we are unknown where to write telemetry".to_string())
+            }
+            write_mode => Ok(Some((url, compression, write_mode))),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TelemetryExporter for AutoDetectTelemetryExporter {
+    fn get_name(&self) -> &str {
+        match self.write_mode.get_write_mode() {
+            WriteMode::Unknown => "auto-detect",
+            WriteMode::Grpc => self.grpc.get_name(),
+            WriteMode::Http => self.http.get_name(),
+        }
+    }
+
+    async fn export(&self, events: &[TelemetryEvent]) -> Result<(), String> {
+        let Some((url, compression, write_mode)) = self.get_write_target().await? else {
+            return Ok(());
+        };
+
+        let written = match write_mode {
+            WriteMode::Grpc => {
+                self.grpc
+                    .write_events(url.as_str(), compression, events)
+                    .await
+            }
+            _ => {
+                self.http
+                    .write_events(url.as_str(), compression, events)
+                    .await
+            }
+        };
+
+        if !written {
+            self.write_mode.set_write_mode(WriteMode::Unknown);
+            return Err(format!("Can not write telemetry events to {}", url));
+        }
+
+        Ok(())
+    }
+
+    async fn export_metrics(&self, metrics: &[TelemetryMetric]) -> Result<(), String> {
+        let Some((url, compression, write_mode)) = self.get_write_target().await? else {
+            return Ok(());
+        };
+
+        let written = match write_mode {
+            WriteMode::Grpc => {
+                self.grpc
+                    .write_metrics(url.as_str(), compression, metrics)
+                    .await
+            }
+            _ => {
+                self.http
+                    .write_metrics(url.as_str(), compression, metrics)
+                    .await
+            }
+        };
+
+        if !written {
+            return Err(format!("Can not write telemetry metrics to {}", url));
+        }
+
+        Ok(())
+    }
+
+    async fn export_log_records(&self, log_records: &[TelemetryLogRecord]) -> Result<(), String> {
+        let Some((url, compression, write_mode)) = self.get_write_target().await? else {
+            return Ok(());
+        };
+
+        let written = match write_mode {
+            WriteMode::Grpc => {
+                self.grpc
+                    .write_log_records(url.as_str(), compression, log_records)
+                    .await
+            }
+            _ => {
+                self.http
+                    .write_log_records(url.as_str(), compression, log_records)
+                    .await
+            }
+        };
+
+        if !written {
+            return Err(format!("Can not write telemetry log records to {}", url));
+        }
+
+        Ok(())
+    }
+
+    async fn shutdown(&self) {
+        self.grpc.shutdown().await;
+        self.http.shutdown().await;
+    }
+}
@@ -0,0 +1,89 @@
+use std::io::Write;
+
+use tonic::codec::CompressionEncoding;
+
+use crate::TelemetryCompression;
+
+impl TelemetryCompression {
+    pub fn get_content_encoding(&self) -> Option<&'static str> {
+        match self {
+            TelemetryCompression::None => None,
+            TelemetryCompression::Gzip => Some("gzip"),
+            TelemetryCompression::Zstd => Some("zstd"),
+        }
+    }
+
+    pub fn get_grpc_encoding(&self) -> Option<CompressionEncoding> {
+        match self {
+            TelemetryCompression::None => None,
+            TelemetryCompression::Gzip => Some(CompressionEncoding::Gzip),
+            TelemetryCompression::Zstd => Some(CompressionEncoding::Zstd),
+        }
+    }
+
+    pub fn compress(&self, data: Vec<u8>) -> Result<Vec<u8>, String> {
+        match self {
+            TelemetryCompression::None => Ok(data),
+            TelemetryCompression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder
+                    .write_all(data.as_slice())
+                    .map_err(|err| format!("Can not gzip telemetry: {}", err))?;
+                encoder
+                    .finish()
+                    .map_err(|err| format!("Can not gzip telemetry: {}", err))
+            }
+            TelemetryCompression::Zstd => zstd::encode_all(data.as_slice(), 0)
+                .map_err(|err| format!("Can not zstd telemetry: {}", err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use crate::TelemetryCompression;
+
+    fn create_payload() -> Vec<u8> {
+        "{\"processId\":1700000000000000,\"eventData\":\"test\"}"
+            .repeat(10)
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let compressed = TelemetryCompression::Gzip
+            .compress(create_payload())
+            .unwrap();
+        assert!(compressed.len() < create_payload().len());
+
+        let mut result = Vec::new();
+        flate2::read::GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut result)
+            .unwrap();
+
+        assert_eq!(result, create_payload());
+    }
+
+    #[test]
+    fn test_zstd_round_trip() {
+        let compressed = TelemetryCompression::Zstd
+            .compress(create_payload())
+            .unwrap();
+        assert!(compressed.len() < create_payload().len());
+
+        let result = zstd::decode_all(compressed.as_slice()).unwrap();
+
+        assert_eq!(result, create_payload());
+    }
+
+    #[test]
+    fn test_none_keeps_data() {
+        let result = TelemetryCompression::None
+            .compress(create_payload())
+            .unwrap();
+        assert_eq!(result, create_payload());
+    }
+}
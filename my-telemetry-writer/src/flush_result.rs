@@ -0,0 +1,11 @@
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TelemetryFlushResult {
+    pub delivered_events: usize,
+    pub not_delivered_events: usize,
+}
+
+impl TelemetryFlushResult {
+    pub fn is_everything_delivered(&self) -> bool {
+        self.not_delivered_events == 0
+    }
+}
@@ -1,15 +1,139 @@
-use std::time::Duration;
+use std::{future::Future, sync::Arc, time::Duration};
 
-use my_telemetry_core::TelemetryEvent;
+use my_telemetry_core::{
+    TelemetryEvent, TelemetryLogRecord, TelemetryMetric, TelemetryMetricValue,
+};
 use tokio::sync::Mutex;
 use tonic::{transport::Channel, Request};
 
-use crate::writer_grpc::{
-    telemetry_writer_client::TelemetryWriterClient, EventGrpcTag, TelemetryGrpcEvent,
+use crate::{
+    settings::get_write_target,
+    writer_grpc::{
+        telemetry_writer_client::TelemetryWriterClient, EventGrpcTag, MetricGrpcKind,
+        TelemetryGrpcEvent, TelemetryGrpcLogRecord, TelemetryGrpcMetric,
+    },
+    MyTelemetrySettings, TelemetryCompression, TelemetryExporter,
 };
 
 const GRPC_TIMEOUT: Duration = Duration::from_secs(3);
 
+pub struct GrpcTelemetryExporter {
+    settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
+    app_name: String,
+    grpc_client: GrpcClient,
+}
+
+impl GrpcTelemetryExporter {
+    pub fn new(
+        app_name: impl Into<String>,
+        settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
+    ) -> Self {
+        Self {
+            settings,
+            app_name: app_name.into(),
+            grpc_client: GrpcClient::new(),
+        }
+    }
+
+    pub(crate) async fn is_grpc(&self, url: &str) -> bool {
+        self.grpc_client.is_grpc(url).await
+    }
+
+    pub(crate) async fn write_events(
+        &self,
+        url: &str,
+        compression: TelemetryCompression,
+        events: &[TelemetryEvent],
+    ) -> bool {
+        self.grpc_client
+            .write_events(self.app_name.as_str(), url.to_string(), compression, events)
+            .await
+    }
+
+    pub(crate) async fn write_metrics(
+        &self,
+        url: &str,
+        compression: TelemetryCompression,
+        metrics: &[TelemetryMetric],
+    ) -> bool {
+        self.grpc_client
+            .write_metrics(
+                self.app_name.as_str(),
+                url.to_string(),
+                compression,
+                metrics,
+            )
+            .await
+    }
+
+    pub(crate) async fn write_log_records(
+        &self,
+        url: &str,
+        compression: TelemetryCompression,
+        log_records: &[TelemetryLogRecord],
+    ) -> bool {
+        self.grpc_client
+            .write_log_records(
+                self.app_name.as_str(),
+                url.to_string(),
+                compression,
+                log_records,
+            )
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl TelemetryExporter for GrpcTelemetryExporter {
+    fn get_name(&self) -> &str {
+        "grpc"
+    }
+
+    async fn export(&self, events: &[TelemetryEvent]) -> Result<(), String> {
+        let Some((url, compression)) = get_write_target(self.settings.as_ref()).await else {
+            return Ok(());
+        };
+
+        if !self.write_events(url.as_str(), compression, events).await {
+            return Err(format!("Can not write telemetry events to {}", url));
+        }
+
+        Ok(())
+    }
+
+    async fn export_metrics(&self, metrics: &[TelemetryMetric]) -> Result<(), String> {
+        let Some((url, compression)) = get_write_target(self.settings.as_ref()).await else {
+            return Ok(());
+        };
+
+        if !self.write_metrics(url.as_str(), compression, metrics).await {
+            return Err(format!("Can not write telemetry metrics to {}", url));
+        }
+
+        Ok(())
+    }
+
+    async fn export_log_records(&self, log_records: &[TelemetryLogRecord]) -> Result<(), String> {
+        let Some((url, compression)) = get_write_target(self.settings.as_ref()).await else {
+            return Ok(());
+        };
+
+        if !self
+            .write_log_records(url.as_str(), compression, log_records)
+            .await
+        {
+            return Err(format!("Can not write telemetry log records to {}", url));
+        }
+
+        Ok(())
+    }
+
+    async fn shutdown(&self) {
+        let mut write_access = self.grpc_client.channel.lock().await;
+        *write_access = None;
+    }
+}
+
 pub struct GrpcClient {
     channel: Mutex<Option<TelemetryWriterClient<Channel>>>,
 }
@@ -48,21 +172,9 @@ impl GrpcClient {
         &self,
         service_name: &str,
         url: String,
-        to_write: Vec<TelemetryEvent>,
+        compression: TelemetryCompression,
+        to_write: &[TelemetryEvent],
     ) -> bool {
-        let mut write_access = self.channel.lock().await;
-
-        if write_access.is_none() {
-            let channel = create_channel(url).await;
-            if channel.is_none() {
-                return false;
-            }
-
-            *write_access = channel;
-        }
-
-        let grpc_channel = write_access.as_mut().unwrap();
-
         let mut grpc_items = Vec::with_capacity(to_write.len());
 
         for item in to_write {
@@ -71,42 +183,183 @@ impl GrpcClient {
                 started_at: item.started,
                 finished_at: item.finished,
                 service_name: service_name.to_string(),
-                event_data: item.data,
-                success: item.success,
-                fail: item.fail,
-                tags: if let Some(tags) = item.tags {
-                    tags.into_iter()
+                event_data: item.data.clone(),
+                success: item.success.clone(),
+                fail: item.fail.clone(),
+                tags: if let Some(tags) = &item.tags {
+                    tags.iter()
                         .map(|x| EventGrpcTag {
-                            key: x.key,
-                            value: x.value,
+                            key: x.key.clone(),
+                            value: x.value.clone(),
                         })
                         .collect()
                 } else {
                     vec![]
                 },
+                span_id: item.span_id,
+                parent_span_id: item.parent_span_id,
             });
         }
 
-        let future = grpc_channel.upload(futures::stream::iter(grpc_items));
+        self.send(url, compression, "events", |mut grpc_channel| async move {
+            grpc_channel.upload(futures::stream::iter(grpc_items)).await
+        })
+        .await
+    }
 
-        let result = tokio::time::timeout(GRPC_TIMEOUT, future).await;
+    pub async fn write_metrics(
+        &self,
+        service_name: &str,
+        url: String,
+        compression: TelemetryCompression,
+        to_write: &[TelemetryMetric],
+    ) -> bool {
+        let grpc_items: Vec<TelemetryGrpcMetric> = to_write
+            .iter()
+            .map(|itm| to_grpc_metric(service_name, itm))
+            .collect();
+
+        self.send(url, compression, "metrics", |mut grpc_channel| async move {
+            grpc_channel
+                .upload_metrics(futures::stream::iter(grpc_items))
+                .await
+        })
+        .await
+    }
 
-        if result.is_err() {
-            println!("Error sending telemetry: Timeout");
-            *write_access = None;
-            return false;
+    pub async fn write_log_records(
+        &self,
+        service_name: &str,
+        url: String,
+        compression: TelemetryCompression,
+        to_write: &[TelemetryLogRecord],
+    ) -> bool {
+        let grpc_items: Vec<TelemetryGrpcLogRecord> = to_write
+            .iter()
+            .map(|itm| TelemetryGrpcLogRecord {
+                process_id: itm.process_id,
+                span_id: itm.span_id,
+                timestamp: itm.timestamp,
+                service_name: service_name.to_string(),
+                level: itm.level.as_str().to_string(),
+                message: itm.message.clone(),
+                tags: if let Some(tags) = &itm.tags {
+                    tags.iter()
+                        .map(|x| EventGrpcTag {
+                            key: x.key.clone(),
+                            value: x.value.clone(),
+                        })
+                        .collect()
+                } else {
+                    vec![]
+                },
+            })
+            .collect();
+
+        self.send(
+            url,
+            compression,
+            "log records",
+            |mut grpc_channel| async move {
+                grpc_channel
+                    .upload_logs(futures::stream::iter(grpc_items))
+                    .await
+            },
+        )
+        .await
+    }
+
+    // Connects on the first write and drops the channel on timeout or error, so the next write
+    // reconnects. Server which does not implement metrics or logs is still fine to write events to
+    async fn send<TResponse, TFuture>(
+        &self,
+        url: String,
+        compression: TelemetryCompression,
+        what: &str,
+        send: impl FnOnce(TelemetryWriterClient<Channel>) -> TFuture,
+    ) -> bool
+    where
+        TFuture: Future<Output = Result<TResponse, tonic::Status>>,
+    {
+        let mut write_access = self.channel.lock().await;
+
+        if write_access.is_none() {
+            let channel = create_channel(url).await;
+            if channel.is_none() {
+                return false;
+            }
+
+            *write_access = channel;
         }
 
-        let result = result.unwrap();
+        let mut grpc_channel = write_access.as_ref().unwrap().clone();
 
-        if let Err(err) = result {
-            println!("Error sending telemetry: {:?}", err);
-            *write_access = None;
-            return false;
+        if let Some(encoding) = compression.get_grpc_encoding() {
+            grpc_channel = grpc_channel.send_compressed(encoding);
+        }
+
+        match tokio::time::timeout(GRPC_TIMEOUT, send(grpc_channel)).await {
+            Ok(Ok(_)) => true,
+            Ok(Err(err)) => {
+                println!("Error sending telemetry {}: {:?}", what, err);
+                if err.code() != tonic::Code::Unimplemented {
+                    *write_access = None;
+                }
+                false
+            }
+            Err(_) => {
+                println!("Error sending telemetry {}: Timeout", what);
+                *write_access = None;
+                false
+            }
         }
+    }
+}
 
-        true
+fn to_grpc_metric(service_name: &str, metric: &TelemetryMetric) -> TelemetryGrpcMetric {
+    let mut result = TelemetryGrpcMetric {
+        service_name: service_name.to_string(),
+        name: metric.name.clone(),
+        kind: MetricGrpcKind::Counter as i32,
+        tags: metric
+            .tags
+            .iter()
+            .map(|x| EventGrpcTag {
+                key: x.key.clone(),
+                value: x.value.clone(),
+            })
+            .collect(),
+        started_at: metric.started,
+        updated_at: metric.updated,
+        value: 0.0,
+        count: 0,
+        sum: 0.0,
+        min: 0.0,
+        max: 0.0,
+        bucket_bounds: vec![],
+        bucket_counts: vec![],
+    };
+
+    match &metric.value {
+        TelemetryMetricValue::Counter(value) => {
+            result.value = *value as f64;
+        }
+        TelemetryMetricValue::Gauge(value) => {
+            result.kind = MetricGrpcKind::Gauge as i32;
+            result.value = *value;
+        }
+        TelemetryMetricValue::Histogram(histogram) => {
+            result.kind = MetricGrpcKind::Histogram as i32;
+            result.count = histogram.count;
+            result.sum = histogram.sum;
+            result.min = histogram.min;
+            result.max = histogram.max;
+            result.bucket_bounds = histogram.bounds.clone();
+            result.bucket_counts = histogram.bucket_counts.clone();
+        }
     }
+
+    result
 }
 
 async fn create_channel(grpc_address: String) -> Option<TelemetryWriterClient<Channel>> {
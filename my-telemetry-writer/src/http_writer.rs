@@ -1,16 +1,119 @@
-use my_telemetry_core::TelemetryEvent;
+use std::sync::Arc;
+
+use my_telemetry_core::{
+    TelemetryEvent, TelemetryLogRecord, TelemetryMetric, TelemetryMetricValue,
+};
 use serde::*;
 
-pub async fn write_as_http(url: &str, app_name: &str, to_write: Vec<TelemetryEvent>) -> bool {
+use crate::{
+    settings::get_write_target, MyTelemetrySettings, TelemetryCompression, TelemetryExporter,
+};
+
+pub struct HttpTelemetryExporter {
+    settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
+    app_name: String,
+}
+
+impl HttpTelemetryExporter {
+    pub fn new(
+        app_name: impl Into<String>,
+        settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
+    ) -> Self {
+        Self {
+            settings,
+            app_name: app_name.into(),
+        }
+    }
+
+    pub(crate) async fn write_events(
+        &self,
+        url: &str,
+        compression: TelemetryCompression,
+        events: &[TelemetryEvent],
+    ) -> bool {
+        write_as_http(url, self.app_name.as_str(), compression, events).await
+    }
+
+    pub(crate) async fn write_metrics(
+        &self,
+        url: &str,
+        compression: TelemetryCompression,
+        metrics: &[TelemetryMetric],
+    ) -> bool {
+        write_metrics_as_http(url, self.app_name.as_str(), compression, metrics).await
+    }
+
+    pub(crate) async fn write_log_records(
+        &self,
+        url: &str,
+        compression: TelemetryCompression,
+        log_records: &[TelemetryLogRecord],
+    ) -> bool {
+        write_log_records_as_http(url, self.app_name.as_str(), compression, log_records).await
+    }
+}
+
+#[async_trait::async_trait]
+impl TelemetryExporter for HttpTelemetryExporter {
+    fn get_name(&self) -> &str {
+        "http"
+    }
+
+    async fn export(&self, events: &[TelemetryEvent]) -> Result<(), String> {
+        let Some((url, compression)) = get_write_target(self.settings.as_ref()).await else {
+            return Ok(());
+        };
+
+        if !self.write_events(url.as_str(), compression, events).await {
+            return Err(format!("Can not write telemetry events to {}", url));
+        }
+
+        Ok(())
+    }
+
+    async fn export_metrics(&self, metrics: &[TelemetryMetric]) -> Result<(), String> {
+        let Some((url, compression)) = get_write_target(self.settings.as_ref()).await else {
+            return Ok(());
+        };
+
+        if !self.write_metrics(url.as_str(), compression, metrics).await {
+            return Err(format!("Can not write telemetry metrics to {}", url));
+        }
+
+        Ok(())
+    }
+
+    async fn export_log_records(&self, log_records: &[TelemetryLogRecord]) -> Result<(), String> {
+        let Some((url, compression)) = get_write_target(self.settings.as_ref()).await else {
+            return Ok(());
+        };
+
+        if !self
+            .write_log_records(url.as_str(), compression, log_records)
+            .await
+        {
+            return Err(format!("Can not write telemetry log records to {}", url));
+        }
+
+        Ok(())
+    }
+}
+
+pub async fn write_as_http(
+    url: &str,
+    app_name: &str,
+    compression: TelemetryCompression,
+    to_write: &[TelemetryEvent],
+) -> bool {
     let mut json_model = Vec::with_capacity(to_write.len());
 
     for itm in to_write {
-        let tags = if let Some(tags_to_write) = itm.tags {
+        let tags = if let Some(tags_to_write) = &itm.tags {
             let mut to_replace = Vec::new();
             for tag_to_write in tags_to_write {
                 to_replace.push(TelemetryHttpTag {
-                    key: tag_to_write.key,
-                    value: tag_to_write.value,
+                    key: tag_to_write.key.clone(),
+                    value: tag_to_write.value.clone(),
                 });
             }
 
@@ -24,24 +127,161 @@ pub async fn write_as_http(url: &str, app_name: &str, to_write: Vec<TelemetryEve
             started: itm.started,
             ended: itm.finished,
             service_name: app_name.to_string(),
-            event_data: itm.data,
-            success: itm.success,
-            fail: itm.fail,
+            event_data: itm.data.clone(),
+            success: itm.success.clone(),
+            fail: itm.fail.clone(),
             ip: None,
             tags,
+            span_id: itm.span_id,
+            parent_span_id: itm.parent_span_id,
+        };
+
+        json_model.push(json_item);
+    }
+
+    post_json(url, "add", compression, &json_model).await
+}
+
+pub async fn write_metrics_as_http(
+    url: &str,
+    app_name: &str,
+    compression: TelemetryCompression,
+    to_write: &[TelemetryMetric],
+) -> bool {
+    let mut json_model = Vec::with_capacity(to_write.len());
+
+    for itm in to_write {
+        let mut json_item = TelemetryMetricHttpModel {
+            service_name: app_name.to_string(),
+            name: itm.name.clone(),
+            kind: "counter",
+            tags: itm
+                .tags
+                .iter()
+                .map(|tag| TelemetryHttpTag {
+                    key: tag.key.clone(),
+                    value: tag.value.clone(),
+                })
+                .collect(),
+            started: itm.started,
+            updated: itm.updated,
+            value: None,
+            count: None,
+            sum: None,
+            min: None,
+            max: None,
+            bucket_bounds: None,
+            bucket_counts: None,
         };
 
+        match &itm.value {
+            TelemetryMetricValue::Counter(value) => {
+                json_item.value = Some(*value as f64);
+            }
+            TelemetryMetricValue::Gauge(value) => {
+                json_item.kind = "gauge";
+                json_item.value = Some(*value);
+            }
+            TelemetryMetricValue::Histogram(histogram) => {
+                json_item.kind = "histogram";
+                json_item.count = Some(histogram.count);
+                json_item.sum = Some(histogram.sum);
+                json_item.min = Some(histogram.min);
+                json_item.max = Some(histogram.max);
+                json_item.bucket_bounds = Some(histogram.bounds.clone());
+                json_item.bucket_counts = Some(histogram.bucket_counts.clone());
+            }
+        }
+
         json_model.push(json_item);
     }
 
+    post_json(url, "metrics", compression, &json_model).await
+}
+
+pub async fn write_log_records_as_http(
+    url: &str,
+    app_name: &str,
+    compression: TelemetryCompression,
+    to_write: &[TelemetryLogRecord],
+) -> bool {
+    let json_model: Vec<TelemetryLogRecordHttpModel> = to_write
+        .iter()
+        .map(|itm| TelemetryLogRecordHttpModel {
+            process_id: itm.process_id,
+            span_id: itm.span_id,
+            timestamp: itm.timestamp,
+            service_name: app_name.to_string(),
+            level: itm.level.as_str(),
+            message: itm.message.clone(),
+            tags: itm.tags.as_ref().map(|tags| {
+                tags.iter()
+                    .map(|tag| TelemetryHttpTag {
+                        key: tag.key.clone(),
+                        value: tag.value.clone(),
+                    })
+                    .collect()
+            }),
+        })
+        .collect();
+
+    post_json(url, "logs", compression, &json_model).await
+}
+
+async fn post_json(
+    url: &str,
+    path_segment: &str,
+    compression: TelemetryCompression,
+    json_model: &impl Serialize,
+) -> bool {
     let flurl = flurl::FlUrl::new(url)
         .append_path_segment("api")
-        .append_path_segment("add")
-        .post(flurl::body::FlUrlBody::as_json(&json_model))
-        .await;
+        .append_path_segment(path_segment);
 
-    if let Err(err) = flurl {
-        println!("Can not write telemetry: {:?}", err);
+    let flurl = match compression.get_content_encoding() {
+        Some(content_encoding) => {
+            let data = serde_json::to_vec(json_model)
+                .map_err(|err| format!("{}", err))
+                .and_then(|data| compression.compress(data));
+
+            let data = match data {
+                Ok(data) => data,
+                Err(err) => {
+                    println!("Can not write telemetry: {}", err);
+                    return false;
+                }
+            };
+
+            flurl
+                .with_header("Content-Encoding", content_encoding)
+                .post(flurl::body::FlUrlBody::Raw {
+                    data,
+                    content_type: Some("application/json"),
+                })
+                .await
+        }
+        None => {
+            flurl
+                .post(flurl::body::FlUrlBody::as_json(json_model))
+                .await
+        }
+    };
+
+    let response = match flurl {
+        Ok(response) => response,
+        Err(err) => {
+            println!("Can not write telemetry: {:?}", err);
+            return false;
+        }
+    };
+
+    // Server which is restarting or does not support the path is not the delivery
+    let status_code = response.get_status_code();
+    if !(200..300).contains(&status_code) {
+        println!(
+            "Can not write telemetry to /api/{}: status code {}",
+            path_segment, status_code
+        );
         return false;
     }
 
@@ -64,9 +304,47 @@ pub struct TelemetryHttpModel {
     pub fail: Option<String>,
     pub ip: Option<String>,
     pub tags: Option<Vec<TelemetryHttpTag>>,
+    #[serde(rename = "spanId")]
+    pub span_id: Option<i64>,
+    #[serde(rename = "parentSpanId")]
+    pub parent_span_id: Option<i64>,
 }
 #[derive(Serialize)]
 pub struct TelemetryHttpTag {
     pub key: String,
     pub value: String,
 }
+
+#[derive(Serialize)]
+pub struct TelemetryMetricHttpModel {
+    #[serde(rename = "serviceName")]
+    pub service_name: String,
+    pub name: String,
+    pub kind: &'static str,
+    pub tags: Vec<TelemetryHttpTag>,
+    pub started: i64,
+    pub updated: i64,
+    pub value: Option<f64>,
+    pub count: Option<u64>,
+    pub sum: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    #[serde(rename = "bucketBounds")]
+    pub bucket_bounds: Option<Vec<f64>>,
+    #[serde(rename = "bucketCounts")]
+    pub bucket_counts: Option<Vec<u64>>,
+}
+
+#[derive(Serialize)]
+pub struct TelemetryLogRecordHttpModel {
+    #[serde(rename = "processId")]
+    pub process_id: i64,
+    #[serde(rename = "spanId")]
+    pub span_id: Option<i64>,
+    pub timestamp: i64,
+    #[serde(rename = "serviceName")]
+    pub service_name: String,
+    pub level: &'static str,
+    pub message: String,
+    pub tags: Option<Vec<TelemetryHttpTag>>,
+}
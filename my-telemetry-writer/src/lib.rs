@@ -1,11 +1,58 @@
+mod auto_detect_exporter;
+mod compression;
+mod flush_result;
 mod grpc_writer;
 mod http_writer;
 mod my_telemetry_writer;
+mod otlp_exporter;
+mod retry_queue;
 mod settings;
+mod tail_sampler;
+mod telemetry_exporter;
 mod write_mode;
+pub use auto_detect_exporter::AutoDetectTelemetryExporter;
+pub use flush_result::TelemetryFlushResult;
+pub use grpc_writer::GrpcTelemetryExporter;
+pub use http_writer::HttpTelemetryExporter;
 pub use my_telemetry_writer::MyTelemetryWriter;
-pub use settings::MyTelemetrySettings;
+pub use otlp_exporter::{OtlpProtocol, OtlpTelemetryExporter};
+pub use retry_queue::TelemetryRetrySettings;
+pub use settings::{MyTelemetrySettings, TelemetryCompression};
+pub use tail_sampler::TelemetryTailSamplingSettings;
+pub use telemetry_exporter::TelemetryExporter;
 
 mod writer_grpc {
     tonic::include_proto!("writer");
 }
+
+pub mod otlp_proto {
+    pub mod opentelemetry {
+        pub mod proto {
+            pub mod common {
+                pub mod v1 {
+                    tonic::include_proto!("opentelemetry.proto.common.v1");
+                }
+            }
+
+            pub mod resource {
+                pub mod v1 {
+                    tonic::include_proto!("opentelemetry.proto.resource.v1");
+                }
+            }
+
+            pub mod trace {
+                pub mod v1 {
+                    tonic::include_proto!("opentelemetry.proto.trace.v1");
+                }
+            }
+
+            pub mod collector {
+                pub mod trace {
+                    pub mod v1 {
+                        tonic::include_proto!("opentelemetry.proto.collector.trace.v1");
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,30 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64},
+        Arc, OnceLock,
+    },
+    time::{Duration, Instant},
+};
 
-use rust_extensions::{ApplicationStates, Logger, MyTimer, MyTimerTick, StrOrString};
+use my_telemetry_core::{PrometheusTextBuilder, TelemetryEvent, TelemetryInterface};
+use rust_extensions::{
+    date_time::DateTimeAsMicroseconds, ApplicationStates, Logger, MyTimer, MyTimerTick, StrOrString,
+};
+use tokio::sync::Mutex;
 
 use crate::{
-    grpc_writer::GrpcClient,
-    write_mode::{WriteMode, WriteModeKeeper},
-    MyTelemetrySettings,
+    retry_queue::{FailedBatch, RetryQueue, RetryQueueStats},
+    tail_sampler::TailSampler,
+    AutoDetectTelemetryExporter, MyTelemetrySettings, TelemetryExporter, TelemetryFlushResult,
+    TelemetryRetrySettings, TelemetryTailSamplingSettings,
 };
 
+const DEFAULT_METRICS_INTERVAL: Duration = Duration::from_secs(10);
+
 pub struct MyTelemetryWriter {
-    timer: MyTimer,
-    telemetry_timer: Arc<TelemetryTimer>,
+    setup: TelemetryWriterSetup,
+    timer: OnceLock<MyTimer>,
+    telemetry_timer: OnceLock<Arc<TelemetryTimer>>,
 }
 
 impl MyTelemetryWriter {
@@ -19,15 +33,201 @@ impl MyTelemetryWriter {
         settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
     ) -> Self {
         let app_name = app_name.into();
-        let mut result = Self {
-            timer: MyTimer::new(Duration::from_secs(1)),
-            telemetry_timer: Arc::new(TelemetryTimer::new(settings, app_name)),
-        };
+        let exporter = AutoDetectTelemetryExporter::new(app_name.as_str(), settings);
+        Self::new_with_exporter(Arc::new(exporter))
+    }
 
-        result
-            .timer
-            .register_timer("TelemetryWriterTimer", result.telemetry_timer.clone());
-        result
+    pub fn new_with_exporter(exporter: Arc<dyn TelemetryExporter + Send + Sync + 'static>) -> Self {
+        Self {
+            setup: TelemetryWriterSetup {
+                exporter,
+                retry_settings: TelemetryRetrySettings::default(),
+                telemetry_interface: my_telemetry_core::TELEMETRY_INTERFACE.clone(),
+                tail_sampling_settings: None,
+                metrics_interval: DEFAULT_METRICS_INTERVAL,
+                export_metrics_and_log_records: false,
+            },
+            timer: OnceLock::new(),
+            telemetry_timer: OnceLock::new(),
+        }
+    }
+
+    pub fn with_retry_settings(mut self, retry_settings: TelemetryRetrySettings) -> Self {
+        self.get_setup_mut().retry_settings = retry_settings;
+        self
+    }
+
+    pub fn with_telemetry_interface(
+        mut self,
+        telemetry_interface: Arc<TelemetryInterface>,
+    ) -> Self {
+        self.get_setup_mut().telemetry_interface = telemetry_interface;
+        self
+    }
+
+    // Events are buffered per process and the whole process is either exported or dropped
+    pub fn with_tail_sampling(mut self, settings: TelemetryTailSamplingSettings) -> Self {
+        self.get_setup_mut().tail_sampling_settings = Some(settings);
+        self
+    }
+
+    // Metrics and log records are not exported by default: the telemetry server has to support them
+    pub fn with_metrics_and_log_records_export(mut self) -> Self {
+        self.get_setup_mut().export_metrics_and_log_records = true;
+        self
+    }
+
+    pub fn with_metrics_interval(mut self, metrics_interval: Duration) -> Self {
+        self.get_setup_mut().metrics_interval = metrics_interval;
+        self
+    }
+
+    // Retry queue and tail sampler live in the timer, so the setup can not be changed after it
+    fn get_setup_mut(&mut self) -> &mut TelemetryWriterSetup {
+        assert!(
+            self.telemetry_timer.get().is_none(),
+            "Telemetry writer has to be set up before it is used"
+        );
+        &mut self.setup
+    }
+
+    fn get_telemetry_timer(&self) -> &Arc<TelemetryTimer> {
+        self.telemetry_timer
+            .get_or_init(|| Arc::new(TelemetryTimer::new(self.setup.clone())))
+    }
+
+    pub fn get_events_to_retry(&self) -> usize {
+        self.get_telemetry_timer().retry_queue_stats.get_events()
+    }
+
+    pub fn get_discarded_events(&self) -> u64 {
+        self.get_telemetry_timer()
+            .retry_queue_stats
+            .get_discarded_events()
+    }
+
+    pub fn get_not_exported_log_records(&self) -> u64 {
+        self.get_telemetry_timer()
+            .not_exported_log_records
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    pub async fn get_events_waiting_for_tail_sampling(&self) -> usize {
+        match &self.get_telemetry_timer().tail_sampler {
+            Some(tail_sampler) => tail_sampler.lock().await.get_pending_events(),
+            None => 0,
+        }
+    }
+
+    pub async fn get_tail_sampled_out_events(&self) -> u64 {
+        match &self.get_telemetry_timer().tail_sampler {
+            Some(tail_sampler) => tail_sampler.lock().await.get_dropped_events(),
+            None => 0,
+        }
+    }
+
+    // Events which did not fit max_pending_events while waiting for the decision
+    pub async fn get_tail_sampling_overflow_events(&self) -> u64 {
+        match &self.get_telemetry_timer().tail_sampler {
+            Some(tail_sampler) => tail_sampler.lock().await.get_overflow_events(),
+            None => 0,
+        }
+    }
+
+    // Writer and collector metrics in the Prometheus text format
+    pub async fn render_prometheus_metrics(&self) -> String {
+        let telemetry_timer = self.get_telemetry_timer();
+        let mut builder = PrometheusTextBuilder::new();
+
+        telemetry_timer
+            .setup
+            .telemetry_interface
+            .write_prometheus_metrics(&mut builder)
+            .await;
+
+        builder.add_gauge(
+            "my_telemetry_writer_exporter_info",
+            "Exporter which is used to write telemetry. Auto-detect exporter shows the write mode",
+            &[("exporter", telemetry_timer.setup.exporter.get_name())],
+            1.0,
+        );
+
+        builder.add_counter(
+            "my_telemetry_writer_exported_events",
+            "Events which were exported",
+            &[],
+            telemetry_timer
+                .exported_events
+                .load(std::sync::atomic::Ordering::Relaxed) as f64,
+        );
+
+        builder.add_counter(
+            "my_telemetry_writer_export_failures",
+            "Export attempts which failed",
+            &[],
+            telemetry_timer
+                .export_failures
+                .load(std::sync::atomic::Ordering::Relaxed) as f64,
+        );
+
+        builder.add_counter(
+            "my_telemetry_writer_not_exported_log_records",
+            "Log records which were dropped because the export failed",
+            &[],
+            telemetry_timer
+                .not_exported_log_records
+                .load(std::sync::atomic::Ordering::Relaxed) as f64,
+        );
+
+        let retry_queue_stats = &telemetry_timer.retry_queue_stats;
+
+        builder.add_gauge(
+            "my_telemetry_writer_retry_queue_batches",
+            "Batches which are waiting to be retried",
+            &[],
+            retry_queue_stats.get_batches() as f64,
+        );
+
+        builder.add_gauge(
+            "my_telemetry_writer_retry_queue_events",
+            "Events which are waiting to be retried",
+            &[],
+            retry_queue_stats.get_events() as f64,
+        );
+
+        builder.add_counter(
+            "my_telemetry_writer_discarded_events",
+            "Events which were discarded after retries",
+            &[],
+            retry_queue_stats.get_discarded_events() as f64,
+        );
+
+        if let Some(tail_sampler) = &telemetry_timer.tail_sampler {
+            let read_access = tail_sampler.lock().await;
+
+            builder.add_gauge(
+                "my_telemetry_writer_tail_sampling_pending_events",
+                "Events which are waiting for the tail sampling decision",
+                &[],
+                read_access.get_pending_events() as f64,
+            );
+
+            builder.add_counter(
+                "my_telemetry_writer_tail_sampled_out_events",
+                "Events which were dropped by the tail sampling",
+                &[],
+                read_access.get_dropped_events() as f64,
+            );
+
+            builder.add_counter(
+                "my_telemetry_writer_tail_sampling_overflow_events",
+                "Events which were dropped because too many events wait for the decision",
+                &[],
+                read_access.get_overflow_events() as f64,
+            );
+        }
+
+        builder.build()
     }
 
     pub fn start(
@@ -35,72 +235,300 @@ impl MyTelemetryWriter {
         app_states: Arc<dyn ApplicationStates + Send + Sync + 'static>,
         logger: Arc<dyn Logger + Send + Sync + 'static>,
     ) {
-        if my_telemetry_core::TELEMETRY_INTERFACE.is_telemetry_set_up() {
+        if self.setup.telemetry_interface.is_telemetry_set_up() {
             return;
         }
 
-        my_telemetry_core::TELEMETRY_INTERFACE
+        let telemetry_timer = self.get_telemetry_timer();
+
+        let mut timer = MyTimer::new(Duration::from_secs(1));
+        timer.register_timer("TelemetryWriterTimer", telemetry_timer.clone());
+
+        if self.timer.set(timer).is_err() {
+            return;
+        }
+
+        self.setup
+            .telemetry_interface
             .writer_is_set
             .store(true, std::sync::atomic::Ordering::SeqCst);
-        self.timer.start(app_states, logger);
-        println!("Telemetry writer is started");
+        let app_states = Arc::new(TelemetryWriterAppStates {
+            app_states,
+            telemetry_timer: telemetry_timer.clone(),
+        });
+
+        if let Some(timer) = self.timer.get() {
+            timer.start(app_states, logger);
+        }
+
+        println!(
+            "Telemetry writer is started with exporter {}",
+            self.setup.exporter.get_name()
+        );
     }
+
+    // Writes everything which is collected so far. Events which were not delivered
+    // before the deadline stay in the retry queue
+    pub async fn flush(&self, deadline: Duration) -> TelemetryFlushResult {
+        self.get_telemetry_timer().flush(deadline, false).await
+    }
+
+    // Stops collecting telemetry and writes everything which is collected so far.
+    // Events which were not delivered before the deadline are discarded
+    pub async fn shutdown(&self, deadline: Duration) -> TelemetryFlushResult {
+        let telemetry_timer = self.get_telemetry_timer();
+
+        self.setup
+            .telemetry_interface
+            .writer_is_set
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+
+        telemetry_timer
+            .is_shut_down
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+
+        let result = telemetry_timer.flush(deadline, true).await;
+
+        self.setup.exporter.shutdown().await;
+
+        println!(
+            "Telemetry writer is stopped. Delivered events: {}. Discarded events: {}",
+            result.delivered_events, result.not_delivered_events
+        );
+
+        result
+    }
+}
+
+#[derive(Clone)]
+struct TelemetryWriterSetup {
+    exporter: Arc<dyn TelemetryExporter + Send + Sync + 'static>,
+    retry_settings: TelemetryRetrySettings,
+    telemetry_interface: Arc<TelemetryInterface>,
+    tail_sampling_settings: Option<TelemetryTailSamplingSettings>,
+    metrics_interval: Duration,
+    export_metrics_and_log_records: bool,
 }
 
 pub struct TelemetryTimer {
-    settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
-    app_name: StrOrString<'static>,
-    write_mode: Arc<WriteModeKeeper>,
-    grpc_client: GrpcClient,
+    setup: TelemetryWriterSetup,
+    retry_queue: Mutex<RetryQueue>,
+    retry_queue_stats: Arc<RetryQueueStats>,
+    tail_sampler: Option<Mutex<TailSampler>>,
+    metrics_exported: Mutex<Option<Instant>>,
+    exported_events: AtomicU64,
+    export_failures: AtomicU64,
+    not_exported_log_records: AtomicU64,
+    is_shut_down: AtomicBool,
 }
 
 impl TelemetryTimer {
-    pub fn new(
-        settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
-        app_name: StrOrString<'static>,
-    ) -> Self {
+    fn new(setup: TelemetryWriterSetup) -> Self {
+        let retry_queue = RetryQueue::new(setup.retry_settings.clone());
+
         Self {
-            app_name,
-            write_mode: Arc::new(WriteModeKeeper::new()),
-            grpc_client: GrpcClient::new(),
-            settings,
+            retry_queue_stats: retry_queue.get_stats(),
+            retry_queue: Mutex::new(retry_queue),
+            tail_sampler: setup
+                .tail_sampling_settings
+                .clone()
+                .map(|settings| Mutex::new(TailSampler::new(settings))),
+            metrics_exported: Mutex::new(None),
+            exported_events: AtomicU64::new(0),
+            export_failures: AtomicU64::new(0),
+            not_exported_log_records: AtomicU64::new(0),
+            is_shut_down: AtomicBool::new(false),
+            setup,
         }
     }
 
-    async fn detect_write_mode(&self, url: &str) {
-        if self.grpc_client.is_grpc(url).await {
-            self.write_mode.set_write_mode(WriteMode::Grpc);
-            return;
+    async fn flush(&self, deadline: Duration, discard_not_delivered: bool) -> TelemetryFlushResult {
+        let started = Instant::now();
+        let now = DateTimeAsMicroseconds::now();
+
+        let mut batches = {
+            let mut retry_queue = self.retry_queue.lock().await;
+            retry_queue.take_all()
+        };
+
+        let events = {
+            let mut write_access = self
+                .setup
+                .telemetry_interface
+                .telemetry_collector
+                .lock()
+                .await;
+            write_access.get_events()
+        };
+
+        // Processes which wait for the tail sampling decision are decided right away
+        let events = match &self.tail_sampler {
+            Some(tail_sampler) => {
+                let mut tail_sampler = tail_sampler.lock().await;
+                let mut result = tail_sampler.add(events.unwrap_or_default(), now);
+                result.extend(tail_sampler.take_all(now));
+                Some(result).filter(|itm| !itm.is_empty())
+            }
+            None => events,
+        };
+
+        if let Some(events) = events {
+            batches.push(FailedBatch::new(events, now));
         }
 
-        self.write_mode.set_write_mode(WriteMode::Http);
-    }
-}
+        let mut result = TelemetryFlushResult::default();
+        let mut failed = Vec::new();
+        let mut not_tried = Vec::new();
 
-#[async_trait::async_trait]
-impl MyTimerTick for TelemetryTimer {
-    async fn tick(&self) {
-        let url = self.settings.get_telemetry_url().await;
+        for batch in batches {
+            let time_left = match deadline.checked_sub(started.elapsed()) {
+                Some(time_left) => time_left,
+                None => {
+                    result.not_delivered_events += batch.events.len();
+                    not_tried.push(batch);
+                    continue;
+                }
+            };
+
+            let exported = tokio::time::timeout(time_left, self.export(batch.events.as_slice()))
+                .await
+                .unwrap_or(false);
+
+            if exported {
+                result.delivered_events += batch.events.len();
+            } else {
+                result.not_delivered_events += batch.events.len();
+                failed.push(batch);
+            }
+        }
+
+        if !discard_not_delivered {
+            let mut retry_queue = self.retry_queue.lock().await;
+
+            for batch in failed {
+                retry_queue.enqueue(batch, now);
+            }
+
+            for batch in not_tried {
+                retry_queue.requeue(batch);
+            }
+        }
+
+        if let Some(time_left) = deadline.checked_sub(started.elapsed()) {
+            let _ = tokio::time::timeout(time_left, self.export_metrics(true)).await;
+        }
+
+        if let Some(time_left) = deadline.checked_sub(started.elapsed()) {
+            let _ = tokio::time::timeout(time_left, self.export_log_records()).await;
+        }
+
+        result
+    }
 
-        if url.is_none() {
-            let mut write_access = my_telemetry_core::TELEMETRY_INTERFACE
+    // Log records are not retried. They are dropped if the export is not enabled
+    async fn export_log_records(&self) {
+        let log_records = {
+            let mut write_access = self
+                .setup
+                .telemetry_interface
                 .telemetry_collector
                 .lock()
                 .await;
-            write_access.clear_events();
+            write_access.get_log_records()
+        };
+
+        let log_records = match log_records {
+            Some(log_records) => log_records,
+            None => return,
+        };
+
+        if !self.setup.export_metrics_and_log_records {
             return;
         }
 
-        let url = url.unwrap();
+        if let Err(err) = self
+            .setup
+            .exporter
+            .export_log_records(log_records.as_slice())
+            .await
+        {
+            self.not_exported_log_records.fetch_add(
+                log_records.len() as u64,
+                std::sync::atomic::Ordering::Relaxed,
+            );
+            println!(
+                "Can not export telemetry log records with exporter {}: {}",
+                self.setup.exporter.get_name(),
+                err
+            );
+        }
+    }
 
-        let to_write = {
-            let write_mode = self.write_mode.get_write_mode();
+    // Metrics are cumulative, so the snapshot which was not exported is covered by the next one
+    async fn export_metrics(&self, force: bool) {
+        if !self.setup.export_metrics_and_log_records {
+            return;
+        }
 
-            if write_mode.is_unknown() {
-                self.detect_write_mode(url.as_str()).await;
+        let mut metrics_exported = self.metrics_exported.lock().await;
+
+        if !force {
+            if let Some(metrics_exported) = metrics_exported.as_ref() {
+                if metrics_exported.elapsed() < self.setup.metrics_interval {
+                    return;
+                }
             }
+        }
+
+        *metrics_exported = Some(Instant::now());
+
+        let metrics = self.setup.telemetry_interface.get_all_metrics();
+
+        if metrics.is_empty() {
+            return;
+        }
+
+        if let Err(err) = self.setup.exporter.export_metrics(metrics.as_slice()).await {
+            println!(
+                "Can not export telemetry metrics with exporter {}: {}",
+                self.setup.exporter.get_name(),
+                err
+            );
+        }
+    }
+
+    async fn export(&self, events: &[TelemetryEvent]) -> bool {
+        if let Err(err) = self.setup.exporter.export(events).await {
+            self.export_failures
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            println!(
+                "Can not export telemetry with exporter {}: {}",
+                self.setup.exporter.get_name(),
+                err
+            );
+            return false;
+        }
+
+        self.exported_events
+            .fetch_add(events.len() as u64, std::sync::atomic::Ordering::Relaxed);
+        true
+    }
+}
+
+#[async_trait::async_trait]
+impl MyTimerTick for TelemetryTimer {
+    async fn tick(&self) {
+        if self.is_shut_down.load(std::sync::atomic::Ordering::Relaxed) {
+            return;
+        }
 
-            let mut write_access = my_telemetry_core::TELEMETRY_INTERFACE
+        self.export_metrics(false).await;
+        self.export_log_records().await;
+
+        let to_write = {
+            let mut write_access = self
+                .setup
+                .telemetry_interface
                 .telemetry_collector
                 .lock()
                 .await;
@@ -108,36 +536,242 @@ impl MyTimerTick for TelemetryTimer {
             write_access.get_events()
         };
 
-        if to_write.is_none() {
-            return;
-        }
+        let now = DateTimeAsMicroseconds::now();
 
-        let to_write = to_write.unwrap();
+        let to_write = match &self.tail_sampler {
+            Some(tail_sampler) => {
+                let to_write = tail_sampler
+                    .lock()
+                    .await
+                    .add(to_write.unwrap_or_default(), now);
+                Some(to_write).filter(|itm| !itm.is_empty())
+            }
+            None => to_write,
+        };
 
-        match self.write_mode.get_write_mode() {
-            WriteMode::Unknown => {
-                println!("Somehow we are unknown where to write telemetry");
+        // Queue is not locked during the export, so the slow endpoint does not block the readers
+        let batches_to_retry = {
+            let mut retry_queue = self.retry_queue.lock().await;
+            retry_queue.get_batches_to_retry(now)
+        };
+
+        let mut batches_to_retry = batches_to_retry.into_iter();
+
+        while let Some(batch) = batches_to_retry.next() {
+            if self.export(batch.events.as_slice()).await {
+                continue;
             }
-            WriteMode::Grpc => {
-                if !self
-                    .grpc_client
-                    .write_events(self.app_name.as_str(), url, to_write)
-                    .await
-                {
-                    self.write_mode.set_write_mode(WriteMode::Unknown);
-                }
+
+            let mut retry_queue = self.retry_queue.lock().await;
+            retry_queue.enqueue(batch, now);
+
+            // Endpoint is not available. There is no reason to try the rest of the batches
+            for batch in batches_to_retry {
+                retry_queue.requeue(batch);
             }
-            WriteMode::Http => {
-                if !crate::http_writer::write_as_http(
-                    url.as_str(),
-                    self.app_name.as_str(),
-                    to_write,
-                )
-                .await
-                {
-                    self.write_mode.set_write_mode(WriteMode::Unknown);
-                }
+
+            if let Some(to_write) = to_write {
+                retry_queue.enqueue_failed(to_write, now);
             }
+
+            return;
         }
+
+        let to_write = match to_write {
+            Some(to_write) => to_write,
+            None => return,
+        };
+
+        if !self.export(to_write.as_slice()).await {
+            let mut retry_queue = self.retry_queue.lock().await;
+            retry_queue.enqueue_failed(to_write, now);
+        }
+    }
+}
+
+// MyTimer stops when the application is shutting down,
+// so the timer of the writer is stopped by the shutdown of the writer as well
+struct TelemetryWriterAppStates {
+    app_states: Arc<dyn ApplicationStates + Send + Sync + 'static>,
+    telemetry_timer: Arc<TelemetryTimer>,
+}
+
+impl ApplicationStates for TelemetryWriterAppStates {
+    fn is_initialized(&self) -> bool {
+        self.app_states.is_initialized()
+    }
+
+    fn is_shutting_down(&self) -> bool {
+        self.app_states.is_shutting_down()
+            || self
+                .telemetry_timer
+                .is_shut_down
+                .load(std::sync::atomic::Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use my_telemetry_core::{
+        TelemetryEvent, TelemetryInterface, TelemetryLogLevel, TelemetryLogRecord, TelemetryMetric,
+    };
+
+    use crate::{MyTelemetryWriter, TelemetryExporter, TelemetryRetrySettings};
+
+    #[derive(Default)]
+    struct FailingExporter {
+        exports: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl TelemetryExporter for FailingExporter {
+        fn get_name(&self) -> &str {
+            "failing"
+        }
+
+        async fn export(&self, _events: &[TelemetryEvent]) -> Result<(), String> {
+            self.exports.fetch_add(1, Ordering::SeqCst);
+            Err("Endpoint is down".to_string())
+        }
+    }
+
+    #[derive(Default)]
+    struct MetricsExporter {
+        metric_exports: AtomicUsize,
+        log_record_exports: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl TelemetryExporter for MetricsExporter {
+        fn get_name(&self) -> &str {
+            "metrics"
+        }
+
+        async fn export(&self, _events: &[TelemetryEvent]) -> Result<(), String> {
+            Ok(())
+        }
+
+        async fn export_metrics(&self, _metrics: &[TelemetryMetric]) -> Result<(), String> {
+            self.metric_exports.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn export_log_records(
+            &self,
+            _log_records: &[TelemetryLogRecord],
+        ) -> Result<(), String> {
+            self.log_record_exports.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn create_log_record() -> TelemetryLogRecord {
+        TelemetryLogRecord {
+            process_id: 1,
+            span_id: None,
+            timestamp: 0,
+            level: TelemetryLogLevel::Info,
+            message: "test".to_string(),
+            tags: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_and_log_records_are_exported_only_if_enabled() {
+        let telemetry_interface = Arc::new(TelemetryInterface::new());
+        telemetry_interface.increment_counter("requests", 1, None);
+        telemetry_interface
+            .write_log_records(vec![create_log_record()])
+            .await;
+
+        let exporter = Arc::new(MetricsExporter::default());
+
+        let writer = MyTelemetryWriter::new_with_exporter(exporter.clone())
+            .with_telemetry_interface(telemetry_interface.clone());
+        writer.flush(Duration::from_secs(1)).await;
+        assert_eq!(exporter.metric_exports.load(Ordering::SeqCst), 0);
+        assert_eq!(exporter.log_record_exports.load(Ordering::SeqCst), 0);
+
+        telemetry_interface
+            .write_log_records(vec![create_log_record()])
+            .await;
+
+        let writer = MyTelemetryWriter::new_with_exporter(exporter.clone())
+            .with_telemetry_interface(telemetry_interface.clone())
+            .with_metrics_and_log_records_export();
+        writer.flush(Duration::from_secs(1)).await;
+        assert_eq!(exporter.metric_exports.load(Ordering::SeqCst), 1);
+        assert_eq!(exporter.log_record_exports.load(Ordering::SeqCst), 1);
+        assert_eq!(writer.get_not_exported_log_records(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_log_records_are_not_exported_by_exporter_without_support() {
+        let telemetry_interface = Arc::new(TelemetryInterface::new());
+        telemetry_interface
+            .write_log_records(vec![create_log_record(), create_log_record()])
+            .await;
+
+        let writer = MyTelemetryWriter::new_with_exporter(Arc::new(FailingExporter::default()))
+            .with_telemetry_interface(telemetry_interface.clone())
+            .with_metrics_and_log_records_export();
+        writer.flush(Duration::from_secs(1)).await;
+
+        assert_eq!(writer.get_not_exported_log_records(), 2);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Telemetry writer has to be set up before it is used")]
+    async fn test_setup_can_not_be_changed_after_use() {
+        let writer = MyTelemetryWriter::new_with_exporter(Arc::new(FailingExporter::default()))
+            .with_telemetry_interface(Arc::new(TelemetryInterface::new()));
+        writer.flush(Duration::from_secs(1)).await;
+
+        let _ = writer.with_retry_settings(TelemetryRetrySettings::default());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_flush_does_not_reset_attempts() {
+        let telemetry_interface = Arc::new(TelemetryInterface::new());
+        let exporter = Arc::new(FailingExporter::default());
+
+        let writer = MyTelemetryWriter::new_with_exporter(exporter.clone())
+            .with_telemetry_interface(telemetry_interface.clone())
+            .with_retry_settings(TelemetryRetrySettings {
+                max_attempts: 3,
+                ..Default::default()
+            });
+
+        telemetry_interface
+            .write_telemetry_event(TelemetryEvent {
+                process_id: 1,
+                started: 0,
+                finished: 0,
+                data: "test".to_string(),
+                success: None,
+                fail: None,
+                tags: None,
+                span_id: None,
+                parent_span_id: None,
+            })
+            .await;
+
+        for _ in 0..2 {
+            let result = writer.flush(Duration::from_secs(1)).await;
+            assert_eq!(result.not_delivered_events, 1);
+            assert_eq!(writer.get_events_to_retry(), 1);
+        }
+
+        writer.flush(Duration::from_secs(1)).await;
+        writer.flush(Duration::from_secs(1)).await;
+
+        // max_attempts counts the first export as well
+        assert_eq!(exporter.exports.load(Ordering::SeqCst), 3);
+        assert_eq!(writer.get_events_to_retry(), 0);
+        assert_eq!(writer.get_discarded_events(), 1);
     }
 }
@@ -0,0 +1,412 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+
+use my_telemetry_core::TelemetryEvent;
+use prost::Message;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+use tonic::transport::Channel;
+
+use crate::{
+    otlp_proto::opentelemetry::proto::{
+        collector::trace::v1::{
+            trace_service_client::TraceServiceClient, ExportTraceServiceRequest,
+        },
+        common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue},
+        resource::v1::Resource,
+        trace::v1::{span::SpanKind, status::StatusCode, ResourceSpans, ScopeSpans, Span, Status},
+    },
+    MyTelemetrySettings, TelemetryCompression, TelemetryExporter,
+};
+
+const OTLP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+const SCOPE_NAME: &str = "my-telemetry";
+
+#[derive(Debug, Clone, Copy)]
+pub enum OtlpProtocol {
+    Grpc,
+    HttpProtobuf,
+    HttpJson,
+}
+
+pub struct OtlpTelemetryExporter {
+    settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
+    app_name: String,
+    protocol: OtlpProtocol,
+    grpc_client: Mutex<Option<TraceServiceClient<Channel>>>,
+}
+
+impl OtlpTelemetryExporter {
+    pub fn new(
+        app_name: impl Into<String>,
+        settings: Arc<dyn MyTelemetrySettings + Send + Sync + 'static>,
+        protocol: OtlpProtocol,
+    ) -> Self {
+        Self {
+            settings,
+            app_name: app_name.into(),
+            protocol,
+            grpc_client: Mutex::new(None),
+        }
+    }
+
+    async fn export_as_grpc(
+        &self,
+        url: String,
+        compression: TelemetryCompression,
+        request: ExportTraceServiceRequest,
+    ) -> Result<(), String> {
+        let mut write_access = self.grpc_client.lock().await;
+
+        if write_access.is_none() {
+            let client = tokio::time::timeout(OTLP_TIMEOUT, TraceServiceClient::connect(url))
+                .await
+                .map_err(|_| "Timeout connecting to OTLP endpoint".to_string())?
+                .map_err(|err| format!("Can not connect to OTLP endpoint: {:?}", err))?;
+
+            *write_access = Some(client);
+        }
+
+        let mut client = write_access.as_ref().unwrap().clone();
+
+        if let Some(encoding) = compression.get_grpc_encoding() {
+            client = client.send_compressed(encoding);
+        }
+
+        let result = tokio::time::timeout(OTLP_TIMEOUT, client.export(request)).await;
+
+        let err = match result {
+            Ok(Ok(_)) => return Ok(()),
+            Ok(Err(err)) => format!("OTLP export error: {:?}", err),
+            Err(_) => "OTLP export timeout".to_string(),
+        };
+
+        *write_access = None;
+        Err(err)
+    }
+
+    async fn export_as_http(
+        &self,
+        url: &str,
+        compression: TelemetryCompression,
+        request: ExportTraceServiceRequest,
+    ) -> Result<(), String> {
+        let (data, content_type) = match self.protocol {
+            OtlpProtocol::HttpJson => (
+                serde_json::to_vec(&to_otlp_json(&request)).map_err(|err| format!("{}", err))?,
+                "application/json",
+            ),
+            _ => (request.encode_to_vec(), "application/x-protobuf"),
+        };
+
+        let data = compression.compress(data)?;
+
+        let mut flurl = flurl::FlUrl::new(url)
+            .append_path_segment("v1")
+            .append_path_segment("traces");
+
+        if let Some(content_encoding) = compression.get_content_encoding() {
+            flurl = flurl.with_header("Content-Encoding", content_encoding);
+        }
+
+        let response = flurl
+            .post(flurl::body::FlUrlBody::Raw {
+                data,
+                content_type: Some(content_type),
+            })
+            .await
+            .map_err(|err| format!("Can not write OTLP telemetry: {:?}", err))?;
+
+        let status_code = response.get_status_code();
+
+        if !(200..300).contains(&status_code) {
+            return Err(format!("OTLP endpoint responded with {}", status_code));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl TelemetryExporter for OtlpTelemetryExporter {
+    fn get_name(&self) -> &str {
+        match self.protocol {
+            OtlpProtocol::Grpc => "otlp-grpc",
+            OtlpProtocol::HttpProtobuf => "otlp-http-protobuf",
+            OtlpProtocol::HttpJson => "otlp-http-json",
+        }
+    }
+
+    async fn export(&self, events: &[TelemetryEvent]) -> Result<(), String> {
+        let url = match self.settings.get_telemetry_url().await {
+            Some(url) => url,
+            None => return Ok(()),
+        };
+
+        let compression = self.settings.get_compression().await;
+        let request = compile_export_request(self.app_name.as_str(), events);
+
+        match self.protocol {
+            OtlpProtocol::Grpc => self.export_as_grpc(url, compression, request).await,
+            OtlpProtocol::HttpProtobuf | OtlpProtocol::HttpJson => {
+                self.export_as_http(url.as_str(), compression, request)
+                    .await
+            }
+        }
+    }
+
+    async fn shutdown(&self) {
+        let mut write_access = self.grpc_client.lock().await;
+        *write_access = None;
+    }
+}
+
+fn compile_export_request(app_name: &str, events: &[TelemetryEvent]) -> ExportTraceServiceRequest {
+    let spans = events.iter().map(to_otlp_span).collect();
+
+    ExportTraceServiceRequest {
+        resource_spans: vec![ResourceSpans {
+            resource: Some(Resource {
+                attributes: vec![string_attribute("service.name", app_name)],
+                dropped_attributes_count: 0,
+            }),
+            scope_spans: vec![ScopeSpans {
+                scope: Some(InstrumentationScope {
+                    name: SCOPE_NAME.to_string(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    ..Default::default()
+                }),
+                spans,
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }],
+    }
+}
+
+fn to_otlp_span(event: &TelemetryEvent) -> Span {
+    let mut attributes = Vec::new();
+
+    if let Some(tags) = &event.tags {
+        for tag in tags {
+            attributes.push(string_attribute(tag.key.as_str(), tag.value.as_str()));
+        }
+    }
+
+    let status = match &event.fail {
+        Some(fail) => Status {
+            message: fail.to_string(),
+            code: StatusCode::Error as i32,
+        },
+        None => {
+            if let Some(success) = &event.success {
+                attributes.push(string_attribute("success", success.as_str()));
+            }
+
+            // Ok is reserved for the status explicitly set by the user
+            Status {
+                message: String::new(),
+                code: StatusCode::Unset as i32,
+            }
+        }
+    };
+
+    let span_id = match event.span_id {
+        Some(span_id) => span_id as u64,
+        None => derive_span_id(event),
+    };
+
+    Span {
+        trace_id: (event.process_id as u64 as u128).to_be_bytes().to_vec(),
+        span_id: span_id.to_be_bytes().to_vec(),
+        parent_span_id: match event.parent_span_id {
+            Some(parent_span_id) => (parent_span_id as u64).to_be_bytes().to_vec(),
+            None => Vec::new(),
+        },
+        name: event.data.to_string(),
+        kind: SpanKind::Internal as i32,
+        start_time_unix_nano: micros_to_nanos(event.started),
+        end_time_unix_nano: micros_to_nanos(event.finished),
+        attributes,
+        status: Some(status),
+        ..Default::default()
+    }
+}
+
+// Events without span id get the id derived from the event, so a retried export gives the same span
+fn derive_span_id(event: &TelemetryEvent) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    event.process_id.hash(&mut hasher);
+    event.started.hash(&mut hasher);
+    event.finished.hash(&mut hasher);
+    event.data.hash(&mut hasher);
+
+    match hasher.finish() {
+        0 => 1,
+        span_id => span_id,
+    }
+}
+
+fn micros_to_nanos(micros: i64) -> u64 {
+    if micros < 0 {
+        return 0;
+    }
+
+    micros as u64 * 1000
+}
+
+fn string_attribute(key: &str, value: &str) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue {
+            value: Some(any_value::Value::StringValue(value.to_string())),
+        }),
+    }
+}
+
+// OTLP/JSON encodes trace and span ids as hex strings and 64 bit integers as strings
+fn to_otlp_json(request: &ExportTraceServiceRequest) -> Value {
+    let resource_spans: Vec<Value> = request
+        .resource_spans
+        .iter()
+        .map(|resource_spans| {
+            let scope_spans: Vec<Value> = resource_spans
+                .scope_spans
+                .iter()
+                .map(|scope_spans| {
+                    let spans: Vec<Value> = scope_spans.spans.iter().map(span_to_json).collect();
+
+                    let scope = scope_spans.scope.as_ref().map(|scope| {
+                        json!({
+                            "name": scope.name,
+                            "version": scope.version,
+                        })
+                    });
+
+                    json!({
+                        "scope": scope,
+                        "spans": spans,
+                    })
+                })
+                .collect();
+
+            let resource = resource_spans.resource.as_ref().map(|resource| {
+                json!({
+                    "attributes": attributes_to_json(&resource.attributes),
+                })
+            });
+
+            json!({
+                "resource": resource,
+                "scopeSpans": scope_spans,
+            })
+        })
+        .collect();
+
+    json!({ "resourceSpans": resource_spans })
+}
+
+fn span_to_json(span: &Span) -> Value {
+    let mut result = json!({
+        "traceId": to_hex(&span.trace_id),
+        "spanId": to_hex(&span.span_id),
+        "name": span.name,
+        "kind": span.kind,
+        "startTimeUnixNano": span.start_time_unix_nano.to_string(),
+        "endTimeUnixNano": span.end_time_unix_nano.to_string(),
+        "attributes": attributes_to_json(&span.attributes),
+    });
+
+    if !span.parent_span_id.is_empty() {
+        result["parentSpanId"] = Value::String(to_hex(&span.parent_span_id));
+    }
+
+    if let Some(status) = &span.status {
+        result["status"] = json!({
+            "code": status.code,
+            "message": status.message,
+        });
+    }
+
+    result
+}
+
+fn attributes_to_json(attributes: &[KeyValue]) -> Value {
+    let attributes: Vec<Value> = attributes
+        .iter()
+        .map(|attribute| {
+            let value = match attribute.value.as_ref().and_then(|itm| itm.value.as_ref()) {
+                Some(any_value::Value::StringValue(value)) => json!({ "stringValue": value }),
+                Some(any_value::Value::BoolValue(value)) => json!({ "boolValue": value }),
+                Some(any_value::Value::IntValue(value)) => {
+                    json!({ "intValue": value.to_string() })
+                }
+                Some(any_value::Value::DoubleValue(value)) => json!({ "doubleValue": value }),
+                _ => json!({}),
+            };
+
+            json!({
+                "key": attribute.key,
+                "value": value,
+            })
+        })
+        .collect();
+
+    Value::Array(attributes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        result.push_str(&format!("{:02x}", b));
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_event(fail: Option<&str>) -> TelemetryEvent {
+        TelemetryEvent {
+            process_id: 1_700_000_000_000_000,
+            started: 1_700_000_000_000_000,
+            finished: 1_700_000_000_000_100,
+            data: "test-event".to_string(),
+            success: None,
+            fail: fail.map(|itm| itm.to_string()),
+            tags: None,
+            span_id: None,
+            parent_span_id: None,
+        }
+    }
+
+    #[test]
+    fn test_span_id_without_event_span_id_is_stable() {
+        let event = create_event(None);
+
+        let first = to_otlp_span(&event);
+        let second = to_otlp_span(&event);
+
+        assert_eq!(first.span_id, second.span_id);
+        assert_ne!(first.span_id, vec![0; 8]);
+
+        let mut other_event = create_event(None);
+        other_event.started += 1;
+        assert_ne!(first.span_id, to_otlp_span(&other_event).span_id);
+    }
+
+    #[test]
+    fn test_status() {
+        let span = to_otlp_span(&create_event(None));
+        assert_eq!(span.status.unwrap().code, StatusCode::Unset as i32);
+
+        let span = to_otlp_span(&create_event(Some("error")));
+        let status = span.status.unwrap();
+        assert_eq!(status.code, StatusCode::Error as i32);
+        assert_eq!(status.message, "error");
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use my_telemetry_core::TelemetryEvent;
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+#[derive(Debug, Clone)]
+pub struct TelemetryRetrySettings {
+    // Export attempts of the batch including the first one
+    pub max_attempts: u32,
+    pub max_age: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub max_queued_events: usize,
+}
+
+impl TelemetryRetrySettings {
+    pub fn no_retries() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    fn get_backoff(&self, attempts: u32) -> Duration {
+        let multiplier = 2u32.saturating_pow(attempts.saturating_sub(1));
+        let backoff = self.initial_backoff.saturating_mul(multiplier);
+
+        if backoff > self.max_backoff {
+            return self.max_backoff;
+        }
+
+        backoff
+    }
+}
+
+impl Default for TelemetryRetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            max_age: Duration::from_secs(60),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            max_queued_events: 100_000,
+        }
+    }
+}
+
+pub struct FailedBatch {
+    pub events: Vec<TelemetryEvent>,
+    pub attempts: u32,
+    pub first_failed: DateTimeAsMicroseconds,
+    pub next_attempt: DateTimeAsMicroseconds,
+}
+
+impl FailedBatch {
+    pub fn new(events: Vec<TelemetryEvent>, now: DateTimeAsMicroseconds) -> Self {
+        Self {
+            events,
+            attempts: 0,
+            first_failed: now,
+            next_attempt: now,
+        }
+    }
+}
+
+// Copy of the queue state which is read without locking the queue
+#[derive(Default)]
+pub struct RetryQueueStats {
+    batches: AtomicUsize,
+    events: AtomicUsize,
+    discarded_events: AtomicU64,
+}
+
+impl RetryQueueStats {
+    pub fn get_batches(&self) -> usize {
+        self.batches.load(Ordering::Relaxed)
+    }
+
+    pub fn get_events(&self) -> usize {
+        self.events.load(Ordering::Relaxed)
+    }
+
+    pub fn get_discarded_events(&self) -> u64 {
+        self.discarded_events.load(Ordering::Relaxed)
+    }
+}
+
+pub struct RetryQueue {
+    settings: TelemetryRetrySettings,
+    batches: VecDeque<FailedBatch>,
+    queued_events: usize,
+    discarded_events: u64,
+    stats: Arc<RetryQueueStats>,
+}
+
+impl RetryQueue {
+    pub fn new(settings: TelemetryRetrySettings) -> Self {
+        Self {
+            settings,
+            batches: VecDeque::new(),
+            queued_events: 0,
+            discarded_events: 0,
+            stats: Arc::new(RetryQueueStats::default()),
+        }
+    }
+
+    pub fn get_stats(&self) -> Arc<RetryQueueStats> {
+        self.stats.clone()
+    }
+
+    fn update_stats(&self) {
+        self.stats
+            .batches
+            .store(self.batches.len(), Ordering::Relaxed);
+        self.stats
+            .events
+            .store(self.queued_events, Ordering::Relaxed);
+        self.stats
+            .discarded_events
+            .store(self.discarded_events, Ordering::Relaxed);
+    }
+
+    pub fn get_batches_to_retry(&mut self, now: DateTimeAsMicroseconds) -> Vec<FailedBatch> {
+        self.discard_expired(now);
+
+        let mut result = Vec::new();
+        let mut not_ready = VecDeque::new();
+
+        while let Some(batch) = self.batches.pop_front() {
+            if batch.next_attempt.unix_microseconds <= now.unix_microseconds {
+                self.queued_events -= batch.events.len();
+                result.push(batch);
+            } else {
+                not_ready.push_back(batch);
+            }
+        }
+
+        self.batches = not_ready;
+        self.update_stats();
+
+        result
+    }
+
+    // Batches keep their attempts and first_failed, so they can be put back with requeue or enqueue
+    pub fn take_all(&mut self) -> Vec<FailedBatch> {
+        self.queued_events = 0;
+        let result = self.batches.drain(..).collect();
+        self.update_stats();
+        result
+    }
+
+    pub fn enqueue_failed(&mut self, events: Vec<TelemetryEvent>, now: DateTimeAsMicroseconds) {
+        self.enqueue(FailedBatch::new(events, now), now);
+    }
+
+    // Puts batch back to the queue after one more unsuccessful attempt
+    pub fn enqueue(&mut self, mut batch: FailedBatch, now: DateTimeAsMicroseconds) {
+        batch.attempts += 1;
+
+        if batch.attempts >= self.settings.max_attempts {
+            self.discarded_events += batch.events.len() as u64;
+            self.update_stats();
+            return;
+        }
+
+        let backoff = self.settings.get_backoff(batch.attempts);
+        batch.next_attempt =
+            DateTimeAsMicroseconds::new(now.unix_microseconds + backoff.as_micros() as i64);
+
+        self.queued_events += batch.events.len();
+        self.batches.push_back(batch);
+
+        while self.queued_events > self.settings.max_queued_events {
+            match self.batches.pop_front() {
+                Some(oldest) => {
+                    self.queued_events -= oldest.events.len();
+                    self.discarded_events += oldest.events.len() as u64;
+                }
+                None => break,
+            }
+        }
+
+        self.update_stats();
+    }
+
+    // Puts back batch which was not tried, the attempt is not counted
+    pub fn requeue(&mut self, batch: FailedBatch) {
+        self.queued_events += batch.events.len();
+        self.batches.push_back(batch);
+        self.update_stats();
+    }
+
+    fn discard_expired(&mut self, now: DateTimeAsMicroseconds) {
+        let max_age = self.settings.max_age.as_micros() as i64;
+
+        let mut discarded = 0;
+
+        self.batches.retain(|batch| {
+            if now.unix_microseconds - batch.first_failed.unix_microseconds <= max_age {
+                return true;
+            }
+
+            discarded += batch.events.len();
+            false
+        });
+
+        self.queued_events -= discarded;
+        self.discarded_events += discarded as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_events(amount: usize) -> Vec<TelemetryEvent> {
+        (0..amount)
+            .map(|process_id| TelemetryEvent {
+                process_id: process_id as i64 + 1,
+                started: 0,
+                finished: 0,
+                data: "test".to_string(),
+                success: None,
+                fail: None,
+                tags: None,
+                span_id: None,
+                parent_span_id: None,
+            })
+            .collect()
+    }
+
+    fn create_settings() -> TelemetryRetrySettings {
+        TelemetryRetrySettings {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_take_all_keeps_attempts() {
+        let now = DateTimeAsMicroseconds::new(1_000_000);
+        let mut retry_queue = RetryQueue::new(create_settings());
+
+        retry_queue.enqueue_failed(create_events(3), now);
+
+        let batches = retry_queue.take_all();
+        assert_eq!(retry_queue.get_stats().get_events(), 0);
+        assert_eq!(batches[0].attempts, 1);
+        assert_eq!(batches[0].first_failed, now);
+
+        for batch in batches {
+            retry_queue.enqueue(batch, now);
+        }
+
+        let batches = retry_queue.take_all();
+        assert_eq!(batches[0].attempts, 2);
+
+        for batch in batches {
+            retry_queue.enqueue(batch, now);
+        }
+
+        assert_eq!(retry_queue.get_stats().get_events(), 0);
+        assert_eq!(retry_queue.get_stats().get_discarded_events(), 3);
+    }
+
+    #[test]
+    fn test_requeue_does_not_count_attempt() {
+        let now = DateTimeAsMicroseconds::new(1_000_000);
+        let mut retry_queue = RetryQueue::new(create_settings());
+
+        retry_queue.enqueue_failed(create_events(2), now);
+
+        for batch in retry_queue.take_all() {
+            retry_queue.requeue(batch);
+        }
+
+        assert_eq!(retry_queue.get_stats().get_batches(), 1);
+        assert_eq!(retry_queue.get_stats().get_events(), 2);
+
+        let batches = retry_queue.get_batches_to_retry(now);
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].attempts, 1);
+    }
+
+    #[test]
+    fn test_expired_batches_are_discarded() {
+        let now = DateTimeAsMicroseconds::new(1_000_000);
+        let mut retry_queue = RetryQueue::new(create_settings());
+
+        retry_queue.enqueue_failed(create_events(2), now);
+
+        let later = DateTimeAsMicroseconds::new(now.unix_microseconds + 61_000_000);
+        assert!(retry_queue.get_batches_to_retry(later).is_empty());
+        assert_eq!(retry_queue.get_stats().get_discarded_events(), 2);
+    }
+}
@@ -1,4 +1,24 @@
+#[derive(Debug, Clone, Copy)]
+pub enum TelemetryCompression {
+    None,
+    Gzip,
+    Zstd,
+}
+
 #[async_trait::async_trait]
 pub trait MyTelemetrySettings {
     async fn get_telemetry_url(&self) -> Option<String>;
+
+    async fn get_compression(&self) -> TelemetryCompression {
+        TelemetryCompression::None
+    }
+}
+
+// No url means telemetry is switched off and there is nothing to write
+pub(crate) async fn get_write_target(
+    settings: &(dyn MyTelemetrySettings + Send + Sync + 'static),
+) -> Option<(String, TelemetryCompression)> {
+    let url = settings.get_telemetry_url().await?;
+    let compression = settings.get_compression().await;
+    Some((url, compression))
 }
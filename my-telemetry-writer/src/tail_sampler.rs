@@ -0,0 +1,279 @@
+use std::{collections::HashMap, time::Duration};
+
+use my_telemetry_core::TelemetryEvent;
+use rust_extensions::date_time::DateTimeAsMicroseconds;
+
+#[derive(Debug, Clone)]
+pub struct TelemetryTailSamplingSettings {
+    // How long events of the process are buffered before the decision is made
+    pub decision_wait: Duration,
+    pub duration_threshold: Option<Duration>,
+    // Part of the processes without fails and slow events which is kept anyway
+    pub keep_ratio: f64,
+    // Events above the limit are dropped before the decision
+    pub max_pending_events: usize,
+}
+
+impl Default for TelemetryTailSamplingSettings {
+    fn default() -> Self {
+        Self {
+            decision_wait: Duration::from_secs(5),
+            duration_threshold: None,
+            keep_ratio: 0.0,
+            max_pending_events: 100_000,
+        }
+    }
+}
+
+struct PendingProcess {
+    first_seen: DateTimeAsMicroseconds,
+    events: Vec<TelemetryEvent>,
+}
+
+pub struct TailSampler {
+    settings: TelemetryTailSamplingSettings,
+    pending: HashMap<i64, PendingProcess>,
+    decided: HashMap<i64, (bool, DateTimeAsMicroseconds)>,
+    pending_events: usize,
+    dropped_events: u64,
+    overflow_events: u64,
+}
+
+impl TailSampler {
+    pub fn new(settings: TelemetryTailSamplingSettings) -> Self {
+        Self {
+            settings,
+            pending: HashMap::new(),
+            decided: HashMap::new(),
+            pending_events: 0,
+            dropped_events: 0,
+            overflow_events: 0,
+        }
+    }
+
+    pub fn get_dropped_events(&self) -> u64 {
+        self.dropped_events
+    }
+
+    pub fn get_overflow_events(&self) -> u64 {
+        self.overflow_events
+    }
+
+    pub fn get_pending_events(&self) -> usize {
+        self.pending_events
+    }
+
+    // Returns events which are ready to be exported
+    pub fn add(
+        &mut self,
+        events: Vec<TelemetryEvent>,
+        now: DateTimeAsMicroseconds,
+    ) -> Vec<TelemetryEvent> {
+        let mut result = Vec::new();
+
+        for event in events {
+            // Events which came after the decision follow it
+            if let Some((keep, _)) = self.decided.get(&event.process_id) {
+                if *keep {
+                    result.push(event);
+                } else {
+                    self.dropped_events += 1;
+                }
+                continue;
+            }
+
+            if self.pending_events >= self.settings.max_pending_events {
+                self.overflow_events += 1;
+                continue;
+            }
+
+            self.pending_events += 1;
+            self.pending
+                .entry(event.process_id)
+                .or_insert_with(|| PendingProcess {
+                    first_seen: now,
+                    events: Vec::new(),
+                })
+                .events
+                .push(event);
+        }
+
+        result.extend(self.decide(now, false));
+
+        result
+    }
+
+    pub fn take_all(&mut self, now: DateTimeAsMicroseconds) -> Vec<TelemetryEvent> {
+        self.decide(now, true)
+    }
+
+    fn decide(&mut self, now: DateTimeAsMicroseconds, decide_all: bool) -> Vec<TelemetryEvent> {
+        let decision_wait = self.settings.decision_wait.as_micros() as i64;
+
+        let ready: Vec<i64> = self
+            .pending
+            .iter()
+            .filter(|(_, itm)| {
+                decide_all
+                    || now.unix_microseconds - itm.first_seen.unix_microseconds >= decision_wait
+            })
+            .map(|(process_id, _)| *process_id)
+            .collect();
+
+        let mut result = Vec::new();
+
+        for process_id in ready {
+            let process = self.pending.remove(&process_id).unwrap();
+            self.pending_events -= process.events.len();
+
+            let keep = self.should_keep(process_id, &process.events);
+            self.decided.insert(process_id, (keep, now));
+
+            if keep {
+                result.extend(process.events);
+            } else {
+                self.dropped_events += process.events.len() as u64;
+            }
+        }
+
+        // Decisions are kept for late events of the same process for one more wait period
+        self.decided.retain(|_, (_, decided_at)| {
+            now.unix_microseconds - decided_at.unix_microseconds < decision_wait
+        });
+
+        result
+    }
+
+    fn should_keep(&self, process_id: i64, events: &[TelemetryEvent]) -> bool {
+        if events.iter().any(|itm| itm.fail.is_some()) {
+            return true;
+        }
+
+        if let Some(duration_threshold) = self.settings.duration_threshold {
+            let duration_threshold = duration_threshold.as_micros() as i64;
+
+            if events
+                .iter()
+                .any(|itm| itm.finished - itm.started > duration_threshold)
+            {
+                return true;
+            }
+        }
+
+        my_telemetry_core::is_in_ratio(process_id, self.settings.keep_ratio)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000_000;
+
+    fn create_settings() -> TelemetryTailSamplingSettings {
+        TelemetryTailSamplingSettings {
+            decision_wait: Duration::from_secs(5),
+            duration_threshold: Some(Duration::from_millis(500)),
+            keep_ratio: 0.0,
+            max_pending_events: 100,
+        }
+    }
+
+    fn create_event(process_id: i64, duration: i64, fail: Option<&str>) -> TelemetryEvent {
+        TelemetryEvent {
+            process_id,
+            started: NOW,
+            finished: NOW + duration,
+            data: "event".to_string(),
+            success: None,
+            fail: fail.map(|itm| itm.to_string()),
+            tags: None,
+            span_id: None,
+            parent_span_id: None,
+        }
+    }
+
+    fn at(seconds: i64) -> DateTimeAsMicroseconds {
+        DateTimeAsMicroseconds::new(NOW + seconds * 1_000_000)
+    }
+
+    #[test]
+    fn test_process_with_fail_is_kept() {
+        let mut tail_sampler = TailSampler::new(create_settings());
+
+        let events = vec![
+            create_event(1, 10, None),
+            create_event(1, 10, Some("error")),
+        ];
+        assert!(tail_sampler.add(events, at(0)).is_empty());
+
+        let result = tail_sampler.add(vec![], at(5));
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(tail_sampler.get_pending_events(), 0);
+        assert_eq!(tail_sampler.get_dropped_events(), 0);
+    }
+
+    #[test]
+    fn test_slow_process_is_kept() {
+        let mut tail_sampler = TailSampler::new(create_settings());
+
+        tail_sampler.add(vec![create_event(1, 600_000, None)], at(0));
+        tail_sampler.add(vec![create_event(2, 400_000, None)], at(0));
+
+        let result = tail_sampler.add(vec![], at(5));
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].process_id, 1);
+        assert_eq!(tail_sampler.get_dropped_events(), 1);
+    }
+
+    #[test]
+    fn test_keep_ratio() {
+        let mut settings = create_settings();
+        settings.keep_ratio = 0.25;
+        settings.max_pending_events = 10_000;
+        let mut tail_sampler = TailSampler::new(settings);
+
+        let events = (1..=10_000).map(|id| create_event(id, 10, None)).collect();
+        tail_sampler.add(events, at(0));
+
+        let kept = tail_sampler.take_all(at(0)).len();
+        assert!((2_200..=2_800).contains(&kept), "kept: {}", kept);
+        assert_eq!(tail_sampler.get_dropped_events(), 10_000 - kept as u64);
+    }
+
+    #[test]
+    fn test_process_is_decided_after_decision_wait() {
+        let mut tail_sampler = TailSampler::new(create_settings());
+
+        tail_sampler.add(vec![create_event(1, 10, Some("error"))], at(0));
+
+        assert!(tail_sampler.add(vec![], at(4)).is_empty());
+        assert_eq!(tail_sampler.get_pending_events(), 1);
+
+        assert_eq!(tail_sampler.add(vec![], at(5)).len(), 1);
+
+        // Late event of the decided process follows the decision right away
+        let result = tail_sampler.add(vec![create_event(1, 10, None)], at(6));
+        assert_eq!(result.len(), 1);
+    }
+
+    #[test]
+    fn test_pending_events_are_limited() {
+        let mut settings = create_settings();
+        settings.max_pending_events = 2;
+        let mut tail_sampler = TailSampler::new(settings);
+
+        let events = (1..=3)
+            .map(|id| create_event(id, 10, Some("error")))
+            .collect();
+        tail_sampler.add(events, at(0));
+
+        assert_eq!(tail_sampler.get_pending_events(), 2);
+        assert_eq!(tail_sampler.get_overflow_events(), 1);
+
+        assert_eq!(tail_sampler.take_all(at(0)).len(), 2);
+        assert_eq!(tail_sampler.get_pending_events(), 0);
+    }
+}
@@ -0,0 +1,25 @@
+use my_telemetry_core::{TelemetryEvent, TelemetryLogRecord, TelemetryMetric};
+
+#[async_trait::async_trait]
+pub trait TelemetryExporter {
+    fn get_name(&self) -> &str;
+
+    async fn export(&self, events: &[TelemetryEvent]) -> Result<(), String>;
+
+    // Exporters which do not support metrics or log records fail, so they are counted as not exported
+    async fn export_metrics(&self, _metrics: &[TelemetryMetric]) -> Result<(), String> {
+        Err(format!(
+            "Exporter {} does not support metrics",
+            self.get_name()
+        ))
+    }
+
+    async fn export_log_records(&self, _log_records: &[TelemetryLogRecord]) -> Result<(), String> {
+        Err(format!(
+            "Exporter {} does not support log records",
+            self.get_name()
+        ))
+    }
+
+    async fn shutdown(&self) {}
+}
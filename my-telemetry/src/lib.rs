@@ -4,3 +4,18 @@ pub use my_telemetry_core::*;
 
 #[cfg(feature = "my-telemetry-writer")]
 pub extern crate my_telemetry_writer;
+
+#[cfg(feature = "my-telemetry-tracing")]
+pub extern crate my_telemetry_tracing;
+
+#[cfg(feature = "my-telemetry-grpc")]
+pub extern crate my_telemetry_grpc;
+
+#[cfg(feature = "my-telemetry-http")]
+pub extern crate my_telemetry_http;
+
+#[cfg(feature = "my-telemetry-flurl")]
+pub extern crate my_telemetry_flurl;
+
+#[cfg(feature = "my-telemetry-macros")]
+pub use my_telemetry_macros::track;